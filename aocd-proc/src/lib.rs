@@ -6,48 +6,415 @@ use syn::{parse_macro_input, Expr, LitInt, Token};
 struct ClientArgs {
     year: u16,
     day: u8,
-    test_input_file: Option<String>,
+    day_span: proc_macro2::Span,
+    test_inputs: Vec<TestInput>,
+    real_input: bool,
+    tests: bool,
+    dry_run: bool,
+    embed: bool,
+}
+
+/// A test input file, and the answers it should give.
+struct TestInput {
+    /// The path as given, relative to the crate root.
+    file: String,
+    expected_answers: Vec<(u8, String)>,
 }
 
 impl Parse for ClientArgs {
     fn parse(input: ParseStream) -> Result<Self> {
-        let help_text = format!(
-            "Provide a year and a day, e.g. #[aocd({})]",
-            chrono::Utc::now().format("%Y, %d")
-        );
+        let help_text = "Provide a year and a day, e.g. #[aocd(2022, 1)]";
 
-        let year = input
-            .parse::<LitInt>()
-            .unwrap_or_else(|_| panic!("Expected a literal year. {help_text}"))
-            .base10_parse::<u16>()?;
-        input
-            .parse::<Token![,]>()
-            .unwrap_or_else(|_| panic!("Expected 2 arguments. {help_text}"));
-        let day = input
-            .parse::<LitInt>()
-            .unwrap_or_else(|_| panic!("Expected a literal day. {help_text}"))
-            .base10_parse::<u8>()?;
-
-        let mut test_input_file = None;
-        if input.parse::<Token![,]>().is_ok() {
-            if let Ok(file_name) = input.parse::<syn::LitStr>() {
-                assert!(
-                    std::fs::metadata(file_name.value()).is_ok(),
-                    "Test file {} does not exist",
-                    file_name.value()
-                );
-                test_input_file = Some(file_name.value());
+        // The year and day are given, taken from the current date with `today`, or inferred.
+        let (year, day, day_span, mut more) = if input.peek(LitInt) {
+            let year = input.parse::<LitInt>()?;
+            let year_span = year.span();
+            let year = year.base10_parse::<u16>()?;
+            if year < FIRST_YEAR {
+                return Err(syn::Error::new(
+                    year_span,
+                    format!("Advent of Code started in {FIRST_YEAR}, so there was none in {year}"),
+                ));
+            }
+            if input.parse::<Token![,]>().is_err() {
+                let span = if input.is_empty() {
+                    year_span
+                } else {
+                    input.span()
+                };
+                return Err(syn::Error::new(
+                    span,
+                    format!("Expected a day after the year. {help_text}"),
+                ));
+            }
+            let day = input.parse::<LitInt>().map_err(|e| {
+                syn::Error::new(e.span(), format!("Expected a literal day. {help_text}"))
+            })?;
+            let day_span = day.span();
+            (year, day.base10_parse::<u8>()?, day_span, false)
+        } else if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "today" {
+            let today = input.parse::<syn::Ident>()?;
+            let (year, day) = today_in_eastern_time(today.span())?;
+            (year, day, today.span(), false)
+        } else {
+            let span = proc_macro2::Span::call_site();
+            let (year, day) = infer_year_and_day(span)?;
+            (year, day, span, !input.is_empty())
+        };
+
+        let mut test_inputs = Vec::new();
+        let mut expected_answers = Vec::new();
+        let mut real_input = false;
+        let mut tests = false;
+        let mut dry_run = false;
+        let mut embed = None;
+        // After an inferred year and day, the options start right away, without a comma.
+        while std::mem::take(&mut more) || input.parse::<Token![,]>().is_ok() {
+            if input.is_empty() {
+                break;
+            }
+            if input.peek(syn::LitStr) || input.peek(syn::token::Bracket) {
+                if !test_inputs.is_empty() {
+                    return Err(input.error("Test inputs were already given"));
+                }
+                test_inputs = parse_test_inputs(input)?;
+            } else if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "real_input"
+            {
+                let flag = input.parse::<syn::Ident>()?;
+                if test_inputs.is_empty() {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "`real_input` runs the puzzle input after the test inputs, so give those first",
+                    ));
+                }
+                real_input = true;
+            } else if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "tests" {
+                input.parse::<syn::Ident>()?;
+                tests = true;
+            } else if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "dry_run" {
+                input.parse::<syn::Ident>()?;
+                dry_run = true;
+            } else if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "embed" {
+                embed = Some(input.parse::<syn::Ident>()?);
+            } else {
+                let (part, answer) = parse_expected_answer(input)?;
+                if test_inputs.len() != 1 {
+                    return Err(syn::Error::new(
+                        answer.span(),
+                        "Expected answers can only be given along with a single test file. \
+                         For several files, give them per file: [(\"example.txt\", part1 = ...)]",
+                    ));
+                }
+                expected_answers.push((part, answer.value()));
+            }
+        }
+        if let [test_input] = test_inputs.as_mut_slice() {
+            test_input.expected_answers.extend(expected_answers);
+        }
+        if let Some(flag) = &embed {
+            if test_inputs.is_empty() {
+                return Err(syn::Error::new(
+                    flag.span(),
+                    "`embed` includes the test inputs in the binary, but none were given",
+                ));
             }
         }
 
         Ok(ClientArgs {
             year,
             day,
-            test_input_file,
+            day_span,
+            test_inputs,
+            real_input,
+            tests,
+            dry_run,
+            embed: embed.is_some(),
+        })
+    }
+}
+
+/// The year of the first Advent of Code, as in `aocd::calendar::FIRST_YEAR`.
+const FIRST_YEAR: u16 = 2015;
+
+/// The last day of the event of the year, or 0 if there was none, as in `aocd::calendar::last_day`,
+/// which can't be used here, since aocd depends on this crate. The generated code checks the day
+/// against that one as well.
+const fn last_day(year: u16) -> u8 {
+    if year < FIRST_YEAR {
+        0
+    } else if year < 2025 {
+        25
+    } else {
+        12
+    }
+}
+
+/// The default pattern for inferring the year and day: a year, and then the first number after it,
+/// like `src/bin/2022-01.rs` or `y2022/src/bin/day01.rs`.
+const DEFAULT_INFER_PATTERN: &str = r"(?:^|\D)(?P<year>20\d\d)\D+?(?P<day>\d{1,2})(?:\D|$)";
+
+/// Infer the year and day from the path of the source file, or else from the package and binary
+/// names, using the regex in `AOC_INFER_PATTERN` if set, with groups named `year` and `day`.
+fn infer_year_and_day(span: proc_macro2::Span) -> Result<(u16, u8)> {
+    let (pattern, source) = match std::env::var("AOC_INFER_PATTERN") {
+        Ok(pattern) if !pattern.is_empty() => (pattern, "AOC_INFER_PATTERN"),
+        _ => (DEFAULT_INFER_PATTERN.to_string(), "the default pattern"),
+    };
+    let pattern = regex::Regex::new(&pattern)
+        .map_err(|e| syn::Error::new(span, format!("Invalid AOC_INFER_PATTERN: {e}")))?;
+
+    let mut subjects = Vec::new();
+    if let Some(file) = proc_macro2::Span::call_site().local_file() {
+        subjects.push(file.to_string_lossy().replace('\\', "/"));
+    }
+    if let Ok(package) = std::env::var("CARGO_PKG_NAME") {
+        match std::env::var("CARGO_BIN_NAME") {
+            Ok(bin) => subjects.push(format!("{package}/{bin}")),
+            Err(_) => subjects.push(package),
+        }
+    }
+
+    subjects
+        .iter()
+        .find_map(|subject| match_year_and_day(&pattern, subject))
+        .ok_or_else(|| {
+            // The whole path is matched, since the year may be in a directory above the crate, but
+            // it's shown relative to the crate root.
+            let root = std::env::var("CARGO_MANIFEST_DIR")
+                .map(|root| format!("{}/", root.replace('\\', "/")))
+                .unwrap_or_default();
+            let shown: Vec<_> = subjects
+                .iter()
+                .map(|subject| subject.strip_prefix(&root).unwrap_or(subject))
+                .collect();
+            syn::Error::new(
+                span,
+                format!(
+                    "Could not infer the year and day from {} with {source}. \
+                     Give them like #[aocd(2022, 1)], use #[aocd(today)], or set AOC_INFER_PATTERN \
+                     to a regex with groups named `year` and `day`",
+                    shown.join(" or "),
+                ),
+            )
+        })
+}
+
+/// The first year and day matched by the pattern in the subject, if the event has that day.
+fn match_year_and_day(pattern: &regex::Regex, subject: &str) -> Option<(u16, u8)> {
+    pattern.captures_iter(subject).find_map(|captures| {
+        let year = captures.name("year")?.as_str().parse::<u16>().ok()?;
+        let day = captures.name("day")?.as_str().parse::<u8>().ok()?;
+        (1..=last_day(year)).contains(&day).then_some((year, day))
+    })
+}
+
+/// The current year and day in US Eastern Standard Time, when the puzzles unlock, if it's December.
+fn today_in_eastern_time(span: proc_macro2::Span) -> Result<(u16, u8)> {
+    use chrono::Datelike;
+
+    let eastern = chrono::FixedOffset::west_opt(5 * 60 * 60).expect("UTC-5 is a valid offset");
+    let today = chrono::Utc::now().with_timezone(&eastern).date_naive();
+    if today.month() != 12 {
+        return Err(syn::Error::new(
+            span,
+            format!("`today` only works in December, and it's {today} in US Eastern time"),
+        ));
+    }
+    let year = u16::try_from(today.year()).expect("The year fits in a u16");
+    let day = u8::try_from(today.day()).expect("The day fits in a u8");
+    if day > last_day(year) {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "`today` is December {day}, but Advent of Code {year} ended on day {}",
+                last_day(year)
+            ),
+        ));
+    }
+    Ok((year, day))
+}
+
+/// Parse a file name, directory or glob, or a list of file names with optional expected answers,
+/// like `["example1.txt", ("example2.txt", part1 = 42)]`.
+fn parse_test_inputs(input: ParseStream) -> Result<Vec<TestInput>> {
+    if input.peek(syn::LitStr) {
+        let pattern = input.parse::<syn::LitStr>()?;
+        return Ok(expand_test_files(&pattern)?
+            .into_iter()
+            .map(|file| TestInput {
+                file,
+                expected_answers: Vec::new(),
+            })
+            .collect());
+    }
+
+    let content;
+    let brackets = syn::bracketed!(content in input);
+    let mut test_inputs = Vec::new();
+    while !content.is_empty() {
+        if content.peek(syn::token::Paren) {
+            let entry;
+            syn::parenthesized!(entry in content);
+            let file = entry.parse::<syn::LitStr>()?;
+            let mut expected_answers = Vec::new();
+            while entry.parse::<Token![,]>().is_ok() && !entry.is_empty() {
+                let (part, answer) = parse_expected_answer(&entry)?;
+                expected_answers.push((part, answer.value()));
+            }
+            if !entry.is_empty() {
+                return Err(entry.error("Expected `(\"file\", part1 = ..., part2 = ...)`"));
+            }
+            test_inputs.push(TestInput {
+                file: existing_file(&file)?,
+                expected_answers,
+            });
+        } else {
+            let file = content.parse::<syn::LitStr>()?;
+            test_inputs.push(TestInput {
+                file: existing_file(&file)?,
+                expected_answers: Vec::new(),
+            });
+        }
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    if test_inputs.is_empty() {
+        return Err(syn::Error::new(
+            brackets.span.join(),
+            "The list of test files is empty",
+        ));
+    }
+    Ok(test_inputs)
+}
+
+fn existing_file(file: &syn::LitStr) -> Result<String> {
+    let path = file.value();
+    if crate_path(&path).is_file() {
+        Ok(path)
+    } else {
+        Err(syn::Error::new(
+            file.span(),
+            format!("Test file {path} does not exist. Test files are relative to the crate root"),
+        ))
+    }
+}
+
+/// The path relative to the root of the crate being compiled, so that it doesn't depend on where
+/// the compiler or the program is run from.
+fn crate_path(path: &str) -> std::path::PathBuf {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    std::path::Path::new(&root).join(path)
+}
+
+/// Expand a file name, a directory (all files in it) or a glob with `*` and `?` in the file name
+/// (e.g. `examples/*.txt`) to the matching files, sorted by name.
+fn expand_test_files(pattern: &syn::LitStr) -> Result<Vec<String>> {
+    let path = pattern.value();
+    let is_glob = path.contains(['*', '?']);
+    if !is_glob && !crate_path(&path).is_dir() {
+        return Ok(vec![existing_file(pattern)?]);
+    }
+
+    let (dir, name_pattern) = if is_glob {
+        match path.rsplit_once('/') {
+            Some((dir, name)) => (dir.to_string(), name.to_string()),
+            None => (".".to_string(), path.clone()),
+        }
+    } else {
+        (path.trim_end_matches('/').to_string(), "*".to_string())
+    };
+    if dir.contains(['*', '?']) {
+        return Err(syn::Error::new(
+            pattern.span(),
+            "Wildcards are only supported in the file name, not in directories",
+        ));
+    }
+
+    if !crate_path(&dir).is_dir() {
+        return Err(syn::Error::new(
+            pattern.span(),
+            format!(
+                "Test directory {dir} does not exist. Test files are relative to the crate root"
+            ),
+        ));
+    }
+    let entries = std::fs::read_dir(crate_path(&dir)).map_err(|e| {
+        syn::Error::new(
+            pattern.span(),
+            format!("Could not read directory {dir}: {e}"),
+        )
+    })?;
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| wildcard_match(name_pattern.as_bytes(), name.as_bytes()))
+        .map(|name| {
+            if dir == "." && !path.starts_with("./") {
+                name
+            } else {
+                format!("{dir}/{name}")
+            }
         })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(syn::Error::new(
+            pattern.span(),
+            format!("No test files match {path}"),
+        ));
+    }
+    Ok(files)
+}
+
+/// Match a name against a pattern where `*` matches any run of characters and `?` any one.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            wildcard_match(&pattern[1..], name)
+                || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
     }
 }
 
+/// Parse `part1 = answer` or `part2 = answer`, where the answer is an integer or string literal.
+fn parse_expected_answer(input: ParseStream) -> Result<(u8, syn::LitStr)> {
+    let key = input.parse::<syn::Ident>()?;
+    let part = match key.to_string().as_str() {
+        "part1" => 1,
+        "part2" => 2,
+        _ => {
+            return Err(syn::Error::new(
+                key.span(),
+                "Expected `part1 = <answer>` or `part2 = <answer>`",
+            ))
+        }
+    };
+    input.parse::<Token![=]>()?;
+
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let answer = match input.parse::<syn::Lit>()? {
+        syn::Lit::Str(answer) if !negative => answer,
+        syn::Lit::Int(answer) => {
+            let sign = if negative { "-" } else { "" };
+            syn::LitStr::new(&format!("{sign}{}", answer.base10_digits()), answer.span())
+        }
+        other => {
+            return Err(syn::Error::new(
+                other.span(),
+                "The expected answer should be an integer or a string literal",
+            ))
+        }
+    };
+    Ok((part, answer))
+}
+
 struct SubmitArgs {
     part: Expr,
     answer: Expr,
@@ -60,8 +427,12 @@ impl Parse for SubmitArgs {
         // If the expr is a literal integer, give an error if it isn't 1 or 2.
         if let Expr::Lit(part_lit) = &part {
             if let syn::Lit::Int(part_int) = &part_lit.lit {
-                if let Ok(part) = part_int.base10_parse::<i64>() {
-                    assert!(part == 1 || part == 2, "Part should be 1 or 2, not {part}",);
+                let part = part_int.base10_parse::<i64>()?;
+                if part != 1 && part != 2 {
+                    return Err(syn::Error::new(
+                        part_int.span(),
+                        format!("Part should be 1 or 2, not {part}"),
+                    ));
                 }
             }
         }
@@ -102,45 +473,268 @@ impl Parse for SubmitArgs {
 /// }
 /// ```
 ///
-/// # Panics
-/// Panics (i.e. surfaces a compile error) if the arguments are not two integers in the expected ranges,
-/// or if the optional third argument is not a string literal containing a valid file name.
+/// Instead of a single file, you can give a directory or a glob like `"examples/*.txt"`, or a list
+/// of files with optional expected answers for each, like
+/// `["example1.txt", ("example2.txt", part1 = 42, part2 = 7)]`. The function is then run once per
+/// file. Add `real_input` at the end to also run it on your puzzle input when all the examples pass.
+/// A run returning an `Err` counts as failed, and stops the runs.
+///
+/// Test files are relative to the root of the crate, where its `Cargo.toml` is, and read when the
+/// program runs. Add `embed` to include them in the binary instead, so that it runs from anywhere
+/// and is rebuilt when they change.
+///
+/// Add `dry_run` to only print the answers for your puzzle input, instead of submitting them.
+///
+/// Add `tests` to also generate a `#[test]` per test file, checking its expected answers, and one
+/// for your puzzle input if that is run too. That one checks the answers against the correct
+/// answers cached when you solved the puzzle, and is skipped if the input isn't cached, so the tests
+/// never contact Advent of Code. The tests also fail if the function returns an `Err`.
+///
+/// Along with a test file, you can give the answers the test input should give, as
+/// `part1 = <answer>` and/or `part2 = <answer>`. The `aocd::submit!` macro then checks the answers
+/// against these, and the program exits with a non-zero status if any of them differ or were never
+/// submitted, which makes the example a proper check, e.g. in CI:
+///
+/// ```ignore
+/// use aocd::prelude::*;
+///
+/// #[aocd(2022, 1, "test_input.txt", part1 = 24000, part2 = 45000)]
+/// fn main() {
+///    ...
+/// }
+/// ```
+///
+/// The year and day can be left out, as in `#[aocd]` or `#[aocd("example.txt")]`, to infer them
+/// from the path of the source file, like `src/bin/2022-01.rs` or `y2022/src/bin/day01.rs`, or
+/// else from the package and binary names. The default pattern takes a year and the first number
+/// after it. Set `AOC_INFER_PATTERN` at compile time to a regex with groups named `year` and `day`
+/// to use another one. With `#[aocd(today)]`, the puzzle unlocked most recently in December is
+/// used instead. Either way, it's a compile error if there's no year and day to be found.
+///
+/// Cargo doesn't know that the code depends on `AOC_INFER_PATTERN`, so changing it doesn't rebuild
+/// anything by itself. Add a `build.rs` printing `cargo:rerun-if-env-changed=AOC_INFER_PATTERN` to
+/// rebuild when it changes.
+///
+/// The day must be part of the event of the year (days 1 to 25 until 2024, and 1 to 12 from 2025 on),
+/// or the program won't compile.
+///
+/// # Errors
+/// Gives a compile error, pointing at the offending argument, if the year and day are not integer
+/// literals (and can't be inferred), if a test file doesn't exist, or if an option is not valid.
 #[proc_macro_attribute]
 pub fn aocd(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(attr as ClientArgs);
+    // On errors, the function is kept as it is, so that it isn't reported missing as well.
+    let item = input.clone();
+    let with_item = |error: syn::Error| {
+        let mut tokens = TokenStream::from(error.to_compile_error());
+        tokens.extend(item.clone());
+        tokens
+    };
+    let args = match syn::parse::<ClientArgs>(attr) {
+        Ok(args) => args,
+        Err(error) => return with_item(error),
+    };
     let year = args.year;
     let day = args.day;
-    let test_input_file = args.test_input_file;
-
-    // When https://github.com/rust-lang/rust/issues/54140 is closed, use that to get nicer error messages.
-    assert!(
-        year >= 2015,
-        "The first Advent of Code was in 2015, not {year}.",
-    );
-    assert!(
-        (1..=25).contains(&day),
-        "Chose a day from 1 to 25, not {day}.",
-    );
-
-    let mut fn_item: syn::ItemFn = syn::parse(input).unwrap();
-    if let Some(test_input_file) = test_input_file {
-        fn_item.block.stmts.insert(
-            0,
-            syn::parse(
-                quote!( let __aocd_client = aocd::Aocd::new(#year, #day, Some(#test_input_file));)
-                    .into(),
-            )
-            .unwrap(),
-        );
+
+    // Which days exist is up to the calendar in aocd, so check the day against it at compile time.
+    let message = format!("Advent of Code {year} has no day {day}");
+    let day_exists = quote::quote_spanned! {args.day_span=>
+        const _: () = assert!(aocd::calendar::day_exists(#year, #day), #message);
+    };
+
+    let mut clients: Vec<_> = args
+        .test_inputs
+        .iter()
+        .map(|test_input| test_client(&args, test_input))
+        .collect();
+    if clients.is_empty() || args.real_input {
+        let dry_run = args.dry_run.then(|| quote!(.with_dry_run(true)));
+        clients.push(quote!(aocd::Aocd::new(#year, #day, None)#dry_run));
+    }
+    let fn_item = parse_macro_input!(input as syn::ItemFn);
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = fn_item;
+    let output = &sig.output;
+    let asyncness = &sig.asyncness;
+    let run_await = asyncness.map(|_| quote!(.await));
+    let solve = quote::format_ident!("__aocd_{}", sig.ident);
+    let solve_fn = quote!(#asyncness fn #solve(__aocd_client: &aocd::Aocd) #output #block);
+
+    // With `tests`, the body is moved to a function next to the annotated one, so that the tests
+    // can call it. With several inputs, it is moved to an inner function, run once per input.
+    let mut generated_tests = None;
+    let mut inner_solve_fn = Some(solve_fn.clone());
+    if (args.tests || clients.len() > 1)
+        && (!sig.inputs.is_empty() || !sig.generics.params.is_empty())
+    {
+        return with_item(syn::Error::new_spanned(
+            &sig,
+            "Tests and several inputs need a function without arguments that isn't generic",
+        ));
+    }
+    if args.tests {
+        if sig.asyncness.is_some() {
+            return with_item(syn::Error::new_spanned(
+                &sig,
+                "Tests can't be generated for an async function",
+            ));
+        }
+        let tests = generate_tests(&args, &sig.ident, &solve);
+        generated_tests = Some(quote!(#solve_fn #tests));
+        inner_solve_fn = None;
+    }
+
+    // The clients report the outcome of any checks when the body is done, however it returns.
+    let body = if let [client] = clients.as_slice() {
+        let run = if args.tests {
+            quote!(#solve(&__aocd_client))
+        } else {
+            let stmts = &block.stmts;
+            quote!(#(#stmts)*)
+        };
+        quote! {
+            let __aocd_client = #client;
+            let __aocd_finish = __aocd_client.finish_on_drop();
+            #run
+        }
     } else {
-        fn_item.block.stmts.insert(
-            0,
-            syn::parse(quote!( let __aocd_client = aocd::Aocd::new(#year, #day, None);).into())
-                .unwrap(),
-        );
+        quote! {
+            #inner_solve_fn
+            let __aocd_clients = [#(#clients),*];
+            let mut __aocd_result = None;
+            for (__aocd_run, __aocd_client) in __aocd_clients.iter().enumerate() {
+                if !__aocd_client.begin(&__aocd_clients[..__aocd_run]) {
+                    break;
+                }
+                let __aocd_run_result = #solve(__aocd_client)#run_await;
+                // An error stops the runs, like a failed example does.
+                let __aocd_error = {
+                    use aocd::outcome::{OtherOutcome as _, ResultOutcome as _};
+                    (&__aocd_run_result).run_error()
+                };
+                __aocd_result = Some(__aocd_run_result);
+                if let Some(error) = __aocd_error {
+                    __aocd_client.fail_run(&error);
+                    break;
+                }
+            }
+            for __aocd_client in &__aocd_clients {
+                __aocd_client.finish();
+            }
+            __aocd_result.expect("The first input is always run")
+        }
+    };
+
+    TokenStream::from(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #day_exists
+            #body
+        }
+
+        #generated_tests
+    })
+}
+
+/// A client for the test input, with its expected answers. The file is read at runtime from the
+/// crate root, or embedded in the binary with `embed`.
+fn test_client(args: &ClientArgs, test_input: &TestInput) -> proc_macro2::TokenStream {
+    let year = args.year;
+    let day = args.day;
+    let file = &test_input.file;
+    let path = crate_path(file).to_string_lossy().into_owned();
+    let expected_answers = test_input
+        .expected_answers
+        .iter()
+        .map(|(part, answer)| quote!(.with_expected_answer(#part, #answer)));
+    if args.embed {
+        quote! {
+            aocd::Aocd::new(#year, #day, Some(#file))
+                .with_test_input(include_str!(#path))
+                #(#expected_answers)*
+        }
+    } else {
+        quote!(aocd::Aocd::new(#year, #day, Some(#path))#(#expected_answers)*)
+    }
+}
+
+/// A test module for the annotated function `name`, whose body is in `solve`, with a test per test
+/// input, checking the expected answers, and one for the real input if that is run too, checking
+/// the answers against the cached correct answers. The tests also fail if the function returns an
+/// `Err`.
+fn generate_tests(
+    args: &ClientArgs,
+    name: &syn::Ident,
+    solve: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    let year = args.year;
+    let day = args.day;
+    let check_result = quote! {
+        if let Some(error) = {
+            use aocd::outcome::{OtherOutcome as _, ResultOutcome as _};
+            (&result).run_error()
+        } {
+            panic!("Failed with an error: {error}");
+        }
+    };
+
+    let mut test_names = Vec::new();
+    let mut tests = Vec::new();
+    for test_input in &args.test_inputs {
+        let file = &test_input.file;
+        let stem = std::path::Path::new(file)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let stem: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let mut test_name = format!("example_{stem}");
+        if test_names.contains(&test_name) {
+            test_name = format!("example_{}_{stem}", test_names.len() + 1);
+        }
+        test_names.push(test_name.clone());
+
+        let test_name = quote::format_ident!("{test_name}");
+        let client = test_client(args, test_input);
+        tests.push(quote! {
+            #[test]
+            fn #test_name() {
+                let client = #client;
+                let result = super::#solve(&client);
+                #check_result
+                assert!(!client.failed(), "Wrong or missing answer for {}", #file);
+            }
+        });
+    }
+    if args.test_inputs.is_empty() || args.real_input {
+        tests.push(quote! {
+            #[test]
+            fn puzzle_input() {
+                let Some(client) = aocd::Aocd::cached(#year, #day) else {
+                    println!("Skipped, since the puzzle input is not cached. Run the solution first.");
+                    return;
+                };
+                let result = super::#solve(&client);
+                #check_result
+                assert!(!client.failed(), "The answers differ from the correct answers");
+            }
+        });
     }
 
-    TokenStream::from(quote!(#fn_item))
+    let module = quote::format_ident!("aocd_{name}");
+    quote! {
+        #[cfg(test)]
+        mod #module {
+            #(#tests)*
+        }
+    }
 }
 
 /// Returns the puzzle input as a String: `input!()`.
@@ -156,7 +750,8 @@ pub fn input(_: TokenStream) -> TokenStream {
 
 /// Submit an answer for the given part: `submit!(part, answer)`.
 ///
-/// This must be used within a function annotated with `#[aocd(year, day)]`.
+/// This must be used within a function annotated with `#[aocd(year, day)]`. The answer can be
+/// anything implementing `aocd::AocAnswer`, e.g. a number, a string, or an `Option` of one.
 ///
 /// If you provide a file name in the function annotation, this just prints the answer without
 /// submitting it to Advent of Code.
@@ -167,3 +762,34 @@ pub fn submit(args: TokenStream) -> TokenStream {
     let answer = args.answer;
     TokenStream::from(quote!(__aocd_client.submit(#part, #answer)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_year_and_day() {
+        let pattern = regex::Regex::new(DEFAULT_INFER_PATTERN).unwrap();
+        let matches = |subject| match_year_and_day(&pattern, subject);
+        assert_eq!(matches("src/bin/2022-01.rs"), Some((2022, 1)));
+        assert_eq!(matches("y2022/src/bin/day01.rs"), Some((2022, 1)));
+        assert_eq!(matches("aoc2015/day25"), Some((2015, 25)));
+        assert_eq!(
+            matches("/home/me/aoc/2023/day-7/src/main.rs"),
+            Some((2023, 7))
+        );
+        assert_eq!(matches("2022-01-part2.rs"), Some((2022, 1)));
+        assert_eq!(matches("src/bin/day01.rs"), None);
+        assert_eq!(matches("src/bin/2022-99.rs"), None);
+        assert_eq!(matches("src/bin/2025-13.rs"), None);
+        assert_eq!(matches("y2025/day20.rs"), None);
+        assert_eq!(matches("src/bin/2025-12.rs"), Some((2025, 12)));
+        assert_eq!(matches("aocd/20221"), None);
+
+        let pattern = regex::Regex::new(r"d(?P<day>\d+)_(?P<year>\d+)").unwrap();
+        assert_eq!(
+            match_year_and_day(&pattern, "src/d3_2016.rs"),
+            Some((2016, 3))
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Conversion of solutions to the answers submitted to Advent of Code.
+//!
+//! [`Aocd::submit`](crate::Aocd::submit) and the `submit!` macro accept anything implementing
+//! [`AocAnswer`], so solutions can return what is natural for them:
+//!
+//! - Integers, strings and `char`s are submitted as they are.
+//! - `Option`s are submitted if `Some`, while `None` is reported and not submitted.
+//! - `Result`s are submitted if `Ok`, while the error is reported and nothing submitted.
+//! - `Vec`s, slices, arrays and tuples are joined with commas, as in e.g. `6,4,8` or `12,34`.
+//!
+//! Implement it for your own types to submit those directly, or wrap any other type implementing
+//! [`std::fmt::Display`] in [`Displayed`], as in `submit!(1, Displayed(big_number))`. Answers used
+//! to only have to implement `std::fmt::Display`, so this is how to keep submitting other such
+//! types.
+//!
+//! # Example
+//! ```
+//! use aocd::AocAnswer;
+//!
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! impl AocAnswer for Point {
+//!     fn to_answer(&self) -> Result<String, String> {
+//!         (self.x, self.y).to_answer()
+//!     }
+//! }
+//!
+//! assert_eq!(Point { x: 3, y: 4 }.to_answer(), Ok("3,4".to_string()));
+//! assert_eq!(Some(vec![1, 2, 3]).to_answer(), Ok("1,2,3".to_string()));
+//! assert!(None::<u64>.to_answer().is_err());
+//! ```
+use std::fmt::{self, Debug};
+
+/// A value that can be submitted as an answer to Advent of Code.
+pub trait AocAnswer {
+    /// The answer to submit, or why there is nothing to submit.
+    ///
+    /// # Errors
+    /// Returns the reason for not submitting anything, e.g. that the solution found no answer.
+    fn to_answer(&self) -> Result<String, String>;
+}
+
+macro_rules! display_answer {
+    ($($t:ty),*) => {
+        $(
+            impl AocAnswer for $t {
+                fn to_answer(&self) -> Result<String, String> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+display_answer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char, str, String);
+
+impl<T: AocAnswer + ?Sized> AocAnswer for &T {
+    fn to_answer(&self) -> Result<String, String> {
+        (**self).to_answer()
+    }
+}
+
+impl<T: AocAnswer> AocAnswer for Option<T> {
+    fn to_answer(&self) -> Result<String, String> {
+        match self {
+            Some(answer) => answer.to_answer(),
+            None => Err("The answer is None".to_string()),
+        }
+    }
+}
+
+impl<T: AocAnswer, E: Debug> AocAnswer for Result<T, E> {
+    fn to_answer(&self) -> Result<String, String> {
+        match self {
+            Ok(answer) => answer.to_answer(),
+            Err(error) => Err(format!("The solution failed: {error:?}")),
+        }
+    }
+}
+
+impl<T: AocAnswer> AocAnswer for [T] {
+    fn to_answer(&self) -> Result<String, String> {
+        Ok(self
+            .iter()
+            .map(AocAnswer::to_answer)
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","))
+    }
+}
+
+impl<T: AocAnswer, const N: usize> AocAnswer for [T; N] {
+    fn to_answer(&self) -> Result<String, String> {
+        self.as_slice().to_answer()
+    }
+}
+
+impl<T: AocAnswer> AocAnswer for Vec<T> {
+    fn to_answer(&self) -> Result<String, String> {
+        self.as_slice().to_answer()
+    }
+}
+
+macro_rules! tuple_answer {
+    ($($t:ident),*) => {
+        impl<$($t: AocAnswer),*> AocAnswer for ($($t,)*) {
+            #[allow(non_snake_case)]
+            fn to_answer(&self) -> Result<String, String> {
+                let ($($t,)*) = self;
+                Ok([$($t.to_answer()?),*].join(","))
+            }
+        }
+    };
+}
+
+tuple_answer!(A, B);
+tuple_answer!(A, B, C);
+tuple_answer!(A, B, C, D);
+
+/// Submits the wrapped value as it is displayed, for types that only implement
+/// [`std::fmt::Display`], like big integers from other crates.
+///
+/// # Example
+/// ```
+/// use aocd::answer::{AocAnswer, Displayed};
+///
+/// assert_eq!(Displayed(true).to_answer(), Ok("true".to_string()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Displayed<T>(pub T);
+
+impl<T: fmt::Display> AocAnswer for Displayed<T> {
+    fn to_answer(&self) -> Result<String, String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_answer() {
+        assert_eq!(42u64.to_answer(), Ok("42".to_string()));
+        assert_eq!((-7i32).to_answer(), Ok("-7".to_string()));
+        assert_eq!("abc".to_answer(), Ok("abc".to_string()));
+        assert_eq!('x'.to_answer(), Ok("x".to_string()));
+        assert_eq!(Some(String::from("ab")).to_answer(), Ok("ab".to_string()));
+        assert_eq!(
+            None::<u8>.to_answer(),
+            Err("The answer is None".to_string())
+        );
+        assert_eq!(Ok::<_, String>(5).to_answer(), Ok("5".to_string()));
+        assert_eq!(
+            Err::<u8, _>("no path").to_answer(),
+            Err("The solution failed: \"no path\"".to_string())
+        );
+        assert_eq!(vec![6, 4, 8].to_answer(), Ok("6,4,8".to_string()));
+        assert_eq!([1, 2].to_answer(), Ok("1,2".to_string()));
+        assert_eq!((12, 34).to_answer(), Ok("12,34".to_string()));
+        assert_eq!((1, "a", 'b').to_answer(), Ok("1,a,b".to_string()));
+        assert_eq!(
+            vec![Some(1), None].to_answer(),
+            Err("The answer is None".to_string())
+        );
+        assert_eq!(Displayed(1.5).to_answer(), Ok("1.5".to_string()));
+        assert_eq!(Some(Displayed(false)).to_answer(), Ok("false".to_string()));
+    }
+}
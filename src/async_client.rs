@@ -0,0 +1,293 @@
+use std::fmt::Display;
+use std::io::BufRead;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::answer::AocAnswer;
+use crate::client::{
+    default_transport, read_confirmation, report_unconfirmed, AnswerOutcome, Aocd, NotYetUnlocked,
+    FINAL_STAR_ANSWER, UNLOCK_RETRIES, UNLOCK_RETRY_DELAY,
+};
+use crate::config::Config;
+use crate::http::{AsyncHttpTransport, Request, Response, SpawnBlocking};
+use crate::part::{to_part, Part};
+use crate::puzzle::Puzzle;
+
+/// An async Aocd client, for use from async code (e.g. on a tokio runtime).
+///
+/// It behaves exactly like the blocking [`Aocd`] client, and shares its cache: inputs and answers
+/// cached by one are known to the other. Requires the `async` feature.
+pub struct AsyncAocd {
+    client: Aocd,
+    transport: Box<dyn AsyncHttpTransport>,
+}
+
+impl AsyncAocd {
+    /// Create a new async Aocd client. See [`Aocd::new`] for details.
+    ///
+    /// By default, requests are sent with the same transport as the blocking client, on tokio's
+    /// blocking thread pool. Use [`AsyncAocd::with_transport`] to use a different one.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::AsyncAocd;
+    ///
+    /// # async fn solve() {
+    /// let client = AsyncAocd::new(2020, 1, None);
+    /// let input = client.get_input().await;
+    /// client.submit(1, input.lines().count()).await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Aocd::new`].
+    #[must_use]
+    pub fn new(year: u16, day: u8, test_file: Option<&str>) -> Self {
+        Self {
+            client: Aocd::new(year, day, test_file),
+            transport: Box::new(SpawnBlocking::new(default_transport(&Config::load()))),
+        }
+    }
+
+    /// Use the given transport for all requests to Advent of Code.
+    #[must_use]
+    pub fn with_transport(mut self, transport: impl AsyncHttpTransport + 'static) -> Self {
+        self.transport = Box::new(transport);
+        self
+    }
+
+    /// Get the puzzle input for the given year and day. See [`Aocd::get_input`].
+    ///
+    /// # Panics
+    /// Panics if the input can't be fetched, e.g. because the puzzle isn't unlocked yet, or the
+    /// Advent of Code server responds with an error.
+    pub async fn get_input(&self) -> String {
+        match self.try_get_input().await {
+            Ok(input) => input,
+            Err(e) if self.client.wait_for_unlock && e.is::<NotYetUnlocked>() => {
+                if let Ok(response) = self.send(self.client.clock_request()).await {
+                    self.client.sync_clock(&response);
+                }
+                while let Some(remaining) = self.client.until_unlock() {
+                    self.client.show_countdown(remaining);
+                    tokio::time::sleep(remaining.min(Duration::from_secs(1))).await;
+                }
+                println!();
+
+                let mut attempt = 0;
+                loop {
+                    match self.try_get_input().await {
+                        Ok(input) => return input,
+                        Err(_) if attempt < UNLOCK_RETRIES => {
+                            tokio::time::sleep(UNLOCK_RETRY_DELAY).await;
+                        }
+                        Err(e) => panic!("Failed to get input: {e:#}"),
+                    }
+                    attempt += 1;
+                }
+            }
+            Err(e) => panic!("Failed to get input: {e:#}"),
+        }
+    }
+
+    /// Get the puzzle input, returning an error instead of panicking. See [`Aocd::try_get_input`].
+    ///
+    /// # Errors
+    /// Returns a [`NotYetUnlocked`] error if the puzzle isn't unlocked yet, and other errors if the
+    /// input can't be fetched.
+    pub async fn try_get_input(&self) -> Result<String> {
+        if let Some(input) = self.client.local_input() {
+            return Ok(input);
+        }
+
+        self.client.check_unlocked()?;
+        let response = self.send(self.client.input_request()).await?;
+        self.client.store_input(&response)
+    }
+
+    /// Get the puzzle description. See [`Aocd::puzzle`].
+    ///
+    /// # Panics
+    /// Panics if the Advent of Code server responds with an error, or the page can't be parsed.
+    pub async fn puzzle(&self) -> Puzzle {
+        if let Some(puzzle) = self.client.local_puzzle() {
+            return puzzle;
+        }
+
+        let response = self
+            .send(self.client.puzzle_page_request())
+            .await
+            .expect("Failed to get puzzle page");
+        self.client
+            .store_puzzle(&response)
+            .expect("Failed to parse puzzle page")
+    }
+
+    /// Submit an answer to the given year, day, and part. See [`Aocd::submit`].
+    ///
+    /// # Panics
+    /// Panics if the part is not 1 or 2, or the Advent of Code server responds to the submission
+    /// with an error.
+    pub async fn submit<P>(&self, part: P, answer: impl AocAnswer)
+    where
+        P: TryInto<Part>,
+        P::Error: Display,
+    {
+        let part = u8::from(to_part(part));
+        let Some(answer) = self.client.answer_string(part, &answer) else {
+            return;
+        };
+
+        let mut looked_up_past_answers = false;
+        while let Some(request) = self.client.unconfirmed_answer_request(part, &answer) {
+            if self.client.confirm
+                && !self
+                    .confirm_submission(part, &answer, || std::io::stdin().lock())
+                    .await
+            {
+                report_unconfirmed(part, &answer);
+                return;
+            }
+            let response = self.send(request).await.expect("Faled to submit answer");
+            if self.client.handle_answer_response(part, &answer, &response) == AnswerOutcome::Done {
+                return;
+            }
+
+            self.client.announce_past_answers_lookup();
+            let cached = match self.send(self.client.puzzle_page_request()).await {
+                Ok(page) => self.client.cache_past_answers(&page),
+                Err(e) => Err(e),
+            };
+            assert!(
+                cached.is_ok() && !looked_up_past_answers,
+                "Failed to cache past answers, even though we thought we had solved this puzzle before. BUG!"
+            );
+            looked_up_past_answers = true;
+        }
+    }
+
+    /// Claim the final star of the year. See [`Aocd::claim_final_star`].
+    ///
+    /// # Panics
+    /// Panics if the client is not for the last day, or the Advent of Code server responds with an error.
+    pub async fn claim_final_star(&self) {
+        if let Some(request) = self.client.final_star_request() {
+            let response = self
+                .send(request)
+                .await
+                .expect("Failed to claim the final star");
+            self.client
+                .handle_answer_response(2, FINAL_STAR_ANSWER, &response);
+        }
+    }
+
+    /// Ask whether to submit the answer, reading the reply from `input` on tokio's blocking thread
+    /// pool, so that waiting for it doesn't block the runtime.
+    async fn confirm_submission<R: BufRead + 'static>(
+        &self,
+        part: u8,
+        answer: &str,
+        input: fn() -> R,
+    ) -> bool {
+        self.client.ask_confirmation(part, answer);
+        tokio::task::spawn_blocking(move || read_confirmation(&mut input()))
+            .await
+            .unwrap_or(false)
+    }
+
+    async fn send(&self, request: Request) -> Result<Response> {
+        let (request, replayed) = self.client.prepare(request)?;
+        if let Some(response) = replayed {
+            return Ok(response);
+        }
+        let mut attempt = 0;
+        loop {
+            tokio::time::sleep(self.client.throttle.reserve()).await;
+            let result = self.transport.send(&request).await;
+            match self.client.retry_delay(&request, attempt, &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => {
+                    let response = result?;
+                    self.client.record(&request, &response)?;
+                    return Ok(response);
+                }
+            }
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_env;
+    use crate::testing::{FakePuzzle, FakeServer};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_input_and_submit() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 1).input("1\n2\n3").answer(1, "6"));
+        let cache_dir = tempdir()?;
+
+        temp_env::with_vars(
+            test_env(cache_dir.path(), &[("AOC_URL", server.url())]),
+            || -> Result<()> {
+                let client = AsyncAocd::new(2022, 1, None);
+                tokio::runtime::Runtime::new()?.block_on(async {
+                    assert_eq!(client.get_input().await, "1\n2\n3");
+                    assert_eq!(client.get_input().await, "1\n2\n3");
+                    client.submit(1, 5).await;
+                    client.submit(1, 6).await;
+                    client.submit(1, 6).await;
+                });
+
+                // The cache is shared with the blocking client.
+                let blocking = Aocd::new(2022, 1, None);
+                assert_eq!(blocking.get_input(), "1\n2\n3");
+                assert_eq!(blocking.cache.get_correct_answer(1)?, "6");
+                Ok(())
+            },
+        )?;
+
+        let requests: Vec<_> = server
+            .requests()
+            .into_iter()
+            .map(|r| format!("{} {}", r.method, r.path))
+            .collect();
+        assert_eq!(
+            requests,
+            vec![
+                "GET /2022/day/1/input",
+                "POST /2022/day/1/answer",
+                "POST /2022/day/1/answer"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_confirm_submission() -> Result<()> {
+        let cache_dir = tempdir()?;
+        temp_env::with_vars(
+            test_env(cache_dir.path(), &[("AOC_URL", "http://localhost:1")]),
+            || -> Result<()> {
+                let client = AsyncAocd::new(2022, 1, None);
+                tokio::runtime::Runtime::new()?.block_on(async {
+                    assert!(
+                        client
+                            .confirm_submission(1, "6", || "yes\n".as_bytes())
+                            .await
+                    );
+                    assert!(
+                        !client
+                            .confirm_submission(1, "6", || "no\n".as_bytes())
+                            .await
+                    );
+                });
+                Ok(())
+            },
+        )
+    }
+}
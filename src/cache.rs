@@ -6,18 +6,24 @@ pub struct Cache {
     cache_directory: String,
 }
 
+/// The directory holding the caches of all sessions.
+pub fn root_directory() -> String {
+    std::env::var("AOC_CACHE_DIR")
+        .or_else(|_| std::env::var("XDG_CACHE_HOME"))
+        .unwrap_or_else(|_| shellexpand::tilde("~/.cache/aocd").to_string())
+}
+
 impl Cache {
     pub fn new(year: u16, day: u8, session: &str) -> Result<Self> {
-        let directory = std::env::var("AOC_CACHE_DIR")
-            .or_else(|_| std::env::var("XDG_CACHE_HOME"))
-            .unwrap_or_else(|_| shellexpand::tilde("~/.cache/aocd").to_string());
-        let directory = format!("{directory}/{session}");
+        let directory = format!("{}/{session}", root_directory());
 
         let inputs_directory = format!("{directory}/inputs");
         let answers_directory = format!("{directory}/answers");
+        let puzzles_directory = format!("{directory}/puzzles");
 
         std::fs::create_dir_all(inputs_directory)?;
         std::fs::create_dir_all(answers_directory)?;
+        std::fs::create_dir_all(puzzles_directory)?;
 
         Ok(Self {
             year,
@@ -45,6 +51,15 @@ impl Cache {
         )
     }
 
+    fn puzzle_cache_file(&self) -> String {
+        format!(
+            "{directory}/puzzles/{year}-{day:02}.html",
+            directory = self.cache_directory,
+            year = self.year,
+            day = self.day
+        )
+    }
+
     pub fn cache_answer_response(
         &self,
         part: u8,
@@ -73,6 +88,25 @@ impl Cache {
         Ok(response)
     }
 
+    /// The wrong answers given for the part, and the responses to them, ordered by answer.
+    pub fn wrong_answers(&self, part: u8) -> Result<Vec<(String, String)>> {
+        let prefix = format!("{}-{:02}-{part}-resp-", self.year, self.day);
+        let correct_answer = self.get_correct_answer(part).ok();
+        let mut answers = Vec::new();
+        for entry in std::fs::read_dir(format!("{}/answers", self.cache_directory))? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(answer) = file_name.to_str().and_then(|f| f.strip_prefix(&prefix)) else {
+                continue;
+            };
+            if correct_answer.as_deref() != Some(answer) {
+                answers.push((answer.to_string(), std::fs::read_to_string(entry.path())?));
+            }
+        }
+        answers.sort();
+        Ok(answers)
+    }
+
     pub fn get_input(&self) -> Result<String> {
         let input = std::fs::read_to_string(self.input_cache_file())?;
         Ok(input)
@@ -82,4 +116,22 @@ impl Cache {
         std::fs::write(self.input_cache_file(), input)?;
         Ok(())
     }
+
+    pub fn get_puzzle_page(&self) -> Result<String> {
+        let page = std::fs::read_to_string(self.puzzle_cache_file())?;
+        Ok(page)
+    }
+
+    pub fn cache_puzzle_page(&self, html: &str) -> Result<()> {
+        std::fs::write(self.puzzle_cache_file(), html)?;
+        Ok(())
+    }
+
+    /// Forget the cached puzzle page, e.g. because solving a part has changed it.
+    pub fn clear_puzzle_page(&self) -> Result<()> {
+        match std::fs::remove_file(self.puzzle_cache_file()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
 }
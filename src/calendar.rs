@@ -0,0 +1,102 @@
+//! Which puzzles each Advent of Code event has.
+//!
+//! The events from 2015 to 2024 have 25 puzzles, and the events from 2025 on have 12. The last
+//! puzzle of an event has only one part to answer: its second star is given for collecting all the
+//! other stars of the event.
+//!
+//! The functions are `const`, so `#[aocd(year, day)]` can check the day at compile time.
+//!
+//! Each puzzle unlocks at midnight US Eastern Standard Time (UTC-5), see [`unlock_time`].
+//!
+//! # Example
+//! ```
+//! use aocd::calendar;
+//!
+//! assert_eq!(calendar::last_day(2024), 25);
+//! assert!(!calendar::day_exists(2025, 13));
+//! assert!(!calendar::has_part_two(2025, 12));
+//! ```
+use std::time::{Duration, SystemTime};
+
+/// The year of the first Advent of Code.
+pub const FIRST_YEAR: u16 = 2015;
+
+/// The last day of the event of the year, or 0 if there was no event.
+#[must_use]
+pub const fn last_day(year: u16) -> u8 {
+    if year < FIRST_YEAR {
+        0
+    } else if year < 2025 {
+        25
+    } else {
+        12
+    }
+}
+
+/// Whether the event of the year has a puzzle on the day.
+#[must_use]
+pub const fn day_exists(year: u16, day: u8) -> bool {
+    day >= 1 && day <= last_day(year)
+}
+
+/// Whether the puzzle has a part 2 to answer, which all but the last one of an event do.
+#[must_use]
+pub const fn has_part_two(year: u16, day: u8) -> bool {
+    day_exists(year, day) && day != last_day(year)
+}
+
+/// When the puzzle unlocks: midnight EST (05:00 UTC) on the day in December.
+#[must_use]
+pub fn unlock_time(year: u16, day: u8) -> SystemTime {
+    let days = days_since_epoch(i64::from(year), 12, i64::from(day));
+    let seconds = days * 24 * 60 * 60 + 5 * 60 * 60;
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.unsigned_abs())
+}
+
+/// The number of days from 1970-01-01 to the date, which must not be before then.
+pub(crate) fn days_since_epoch(year: i64, month: i64, day: i64) -> i64 {
+    // From Howard Hinnant's `days_from_civil`, with March as the first month of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar() {
+        assert_eq!(last_day(2014), 0);
+        assert!(!day_exists(2014, 1));
+        assert!(day_exists(2015, 1));
+        assert!(day_exists(2024, 25));
+        assert!(!day_exists(2024, 26));
+        assert!(!day_exists(2024, 0));
+        assert!(day_exists(2025, 12));
+        assert!(!day_exists(2025, 13));
+        assert!(has_part_two(2022, 24));
+        assert!(!has_part_two(2022, 25));
+        assert!(has_part_two(2025, 11));
+        assert!(!has_part_two(2025, 12));
+    }
+
+    #[test]
+    fn test_unlock_time() {
+        assert_eq!(days_since_epoch(1970, 1, 1), 0);
+        assert_eq!(days_since_epoch(2000, 3, 1), 11_017);
+        // 2022-12-01T05:00:00Z
+        assert_eq!(
+            unlock_time(2022, 1),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_669_870_800)
+        );
+        // 2024-12-25T05:00:00Z
+        assert_eq!(
+            unlock_time(2024, 25),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_102_800)
+        );
+    }
+}
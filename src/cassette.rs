@@ -0,0 +1,132 @@
+//! Recording and replaying of the HTTP interactions of the client.
+//!
+//! Set `AOC_RECORD` to a file path to save every request and response to that file (a
+//! "cassette"), or set `AOC_REPLAY` to serve responses from a previously recorded cassette,
+//! without touching the network. Session cookies are redacted before anything is written.
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{Request, Response};
+
+const REDACTED: &str = "<redacted>";
+
+/// A recorded request and its response. The request URL is stored relative to the base URL of
+/// the client, so a cassette can be replayed against any base URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    request: Request,
+    response: Response,
+}
+
+pub enum Cassette {
+    Record {
+        path: String,
+        interactions: Mutex<Vec<Interaction>>,
+    },
+    Replay {
+        path: String,
+        /// Interactions not yet replayed, in recorded order.
+        interactions: Mutex<Vec<Interaction>>,
+    },
+}
+
+impl Cassette {
+    /// Set up recording or replaying according to `AOC_RECORD` or `AOC_REPLAY`, if either is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(path) = std::env::var("AOC_REPLAY") {
+            return Self::replaying(&path).map(Some);
+        }
+        if let Ok(path) = std::env::var("AOC_RECORD") {
+            return Ok(Some(Self::recording(&path)));
+        }
+        Ok(None)
+    }
+
+    /// Record to the given file. Anything already recorded there is kept, and new interactions
+    /// are appended.
+    pub fn recording(path: &str) -> Self {
+        let interactions = read_interactions(path).unwrap_or_default();
+        Cassette::Record {
+            path: path.to_string(),
+            interactions: Mutex::new(interactions),
+        }
+    }
+
+    pub fn replaying(path: &str) -> Result<Self> {
+        Ok(Cassette::Replay {
+            path: path.to_string(),
+            interactions: Mutex::new(read_interactions(path)?),
+        })
+    }
+
+    /// The recorded response to the request, if replaying. It is an error if there is none.
+    pub fn replay(&self, base_url: &str, request: &Request) -> Result<Option<Response>> {
+        let Cassette::Replay { path, interactions } = self else {
+            return Ok(None);
+        };
+        let relative_url = relative_url(base_url, request);
+        let mut interactions = interactions
+            .lock()
+            .expect("Cassette should not be poisoned");
+        let position = interactions
+            .iter()
+            .position(|i| {
+                i.request.method == request.method
+                    && i.request.url == relative_url
+                    && i.request.body == request.body
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "No recorded response for {:?} {relative_url} left in cassette {path}",
+                    request.method
+                )
+            })?;
+        Ok(Some(interactions.remove(position).response))
+    }
+
+    /// Save the request and its response, if recording.
+    pub fn record(&self, base_url: &str, request: &Request, response: &Response) -> Result<()> {
+        let Cassette::Record { path, interactions } = self else {
+            return Ok(());
+        };
+        let mut interactions = interactions
+            .lock()
+            .expect("Cassette should not be poisoned");
+        interactions.push(redact(Interaction {
+            request: Request {
+                url: relative_url(base_url, request).to_string(),
+                ..request.clone()
+            },
+            response: response.clone(),
+        }));
+        // Write after every interaction, so a panicking client still leaves a cassette behind.
+        std::fs::write(path, serde_json::to_string_pretty(&*interactions)?)
+            .with_context(|| format!("Failed to write cassette {path}"))
+    }
+}
+
+fn relative_url<'a>(base_url: &str, request: &'a Request) -> &'a str {
+    request.url.strip_prefix(base_url).unwrap_or(&request.url)
+}
+
+fn read_interactions(path: &str) -> Result<Vec<Interaction>> {
+    let cassette =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read cassette {path}"))?;
+    serde_json::from_str(&cassette).with_context(|| format!("Failed to parse cassette {path}"))
+}
+
+fn redact(mut interaction: Interaction) -> Interaction {
+    for (name, value) in interaction
+        .request
+        .headers
+        .iter_mut()
+        .chain(interaction.response.headers.iter_mut())
+    {
+        if name.eq_ignore_ascii_case("cookie") || name.eq_ignore_ascii_case("set-cookie") {
+            *value = REDACTED.to_string();
+        }
+    }
+    interaction
+}
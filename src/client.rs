@@ -1,16 +1,53 @@
 use std::fmt::Display;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::{Duration, SystemTime};
 
+use crate::answer::AocAnswer;
 use crate::cache;
+use crate::calendar;
+use crate::cassette::Cassette;
+use crate::config::Config;
+use crate::http::{parse_http_date, HttpTransport, Method, MinreqTransport, Request, Response};
+use crate::part::{to_part, Part};
+use crate::puzzle::Puzzle;
+use crate::throttle::Throttle;
 use anyhow::{anyhow, Result};
-use regex::Regex;
 
 pub struct Aocd {
     year: u16,
     day: u8,
     url: String,
     session_token: String,
-    cache: cache::Cache,
+    user_agent: String,
+    pub(crate) cache: cache::Cache,
+    pub(crate) throttle: Throttle,
+    retries: u32,
+    retry_delay: Duration,
+    transport: Box<dyn HttpTransport>,
+    cassette: Option<Cassette>,
     test_file: Option<String>,
+    /// The content of the test file, if it was given instead of read from the file.
+    test_input: Option<String>,
+    expected_answers: [Option<String>; 2],
+    /// Which parts with an expected answer have been checked against it.
+    checked_parts: [AtomicBool; 2],
+    /// Check submitted answers against the cached correct answers instead of submitting them.
+    verify: bool,
+    /// Report submitted answers instead of submitting them.
+    dry_run: bool,
+    /// Ask before submitting answers.
+    pub(crate) confirm: bool,
+    /// Refuse to submit answers that look like mistakes.
+    check_answers: bool,
+    /// Never send requests to Advent of Code.
+    offline: bool,
+    /// Wait for the puzzle to unlock when getting the input too early.
+    pub(crate) wait_for_unlock: bool,
+    /// How far the server's clock is ahead of ours, in milliseconds.
+    clock_offset: AtomicI64,
+    failed: AtomicBool,
 }
 
 impl Aocd {
@@ -20,10 +57,40 @@ impl Aocd {
     /// It will also require write access to `~/.cache/aocd` to cache puzzle inputs and answers.
     ///
     /// Alternatively, if a test file is provided, the client will just be a thin wrapper using the
-    /// file as input and simply printing answers to stdout.
+    /// file as input and simply printing answers to stdout. No session cookie is needed then.
+    ///
+    /// The client talks to `https://adventofcode.com`, unless another base URL is given in the
+    /// `AOC_URL` environment variable (e.g. a local `aocd::testing::FakeServer`).
+    ///
+    /// Requests identify the client with a User-Agent including the `contact` setting (e.g. your
+    /// email address), and are spaced out by at least the `throttle` setting (in seconds, default 1),
+    /// also across processes. See the README for how to configure these.
+    ///
+    /// Failing GET requests are retried (`retries` times, default 3) with exponential backoff,
+    /// starting at `retry_delay` seconds (default 1). Answer submissions are never retried.
+    /// The `timeout` setting (default 30 seconds) limits how long a whole request may take.
+    ///
+    /// With the `verify` setting on (e.g. `AOC_VERIFY=1`), answers are never submitted, but checked
+    /// against the correct answers cached when the puzzle was solved. See [`Self::with_verify`].
+    ///
+    /// With the `dry_run` setting on, answers are only printed, along with what is known about
+    /// them. See [`Self::with_dry_run`].
+    ///
+    /// Answers that look like mistakes (e.g. empty, `0`, or `Some(42)`) are not submitted, unless
+    /// the `check_answers` setting is off. See [`Self::with_answer_checks`].
+    ///
+    /// Before submitting an answer, you're asked to confirm it when running in a terminal, unless
+    /// the `confirm` setting is off. The previous guesses for the part are shown along with it.
+    ///
+    /// Input can't be fetched before the puzzle unlocks, at midnight EST. With the `wait_for_unlock`
+    /// setting on, [`Self::get_input`] then waits for it. See [`Self::with_wait_for_unlock`].
+    ///
+    /// Setting `AOC_RECORD` to a file path saves all requests and responses to that file (with the
+    /// session cookie redacted), and setting `AOC_REPLAY` serves responses from such a file instead
+    /// of the network.
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use aocd::Aocd;
     ///
     /// let client = Aocd::new(2020, 1, None);
@@ -31,74 +98,715 @@ impl Aocd {
     /// ```
     ///
     /// # Panics
-    /// Panics if the session cookie is not found or the cache could not be successfully setup/initialized.
+    /// Panics if the day is not part of the event of the year, the session cookie is not found, the
+    /// cache could not be successfully setup/initialized, or a cassette to replay could not be read.
     #[must_use]
     pub fn new(year: u16, day: u8, test_file: Option<&str>) -> Self {
-        let session_token = find_aoc_token();
+        assert!(
+            calendar::day_exists(year, day),
+            "Advent of Code {year} has no day {day}. The days are 1 to {}.",
+            calendar::last_day(year)
+        );
+        let session_token = match test_file {
+            Some(_) => try_find_aoc_token().unwrap_or_else(|| "no-session".to_string()),
+            None => find_aoc_token(),
+        };
         let cache = cache::Cache::new(year, day, &session_token)
             .expect("Should be able to create cache for aocd");
 
         #[cfg(not(test))]
-        let url = "https://adventofcode.com".to_string();
+        let default_url = "https://adventofcode.com".to_string();
         #[cfg(test)]
-        let url = mockito::server_url();
+        let default_url = mockito::server_url();
+        let config = Config::load();
+        let url = config.get("url").unwrap_or(default_url);
+        let user_agent = config
+            .get("user_agent")
+            .unwrap_or_else(|| default_user_agent(config.get("contact").as_deref()));
+        let throttle = Throttle::new(
+            config
+                .duration("throttle")
+                .unwrap_or(Duration::from_secs(1)),
+            format!("{}/last-request", cache::root_directory()).into(),
+        );
+        let retries = config.parse("retries").unwrap_or(3);
+        let retry_delay = config
+            .duration("retry_delay")
+            .unwrap_or(Duration::from_secs(1));
+        let transport = default_transport(&config);
+        let cassette = Cassette::from_env().expect("Should be able to read cassette for aocd");
+        let verify = config.flag("verify").unwrap_or(false);
+        let dry_run = config.flag("dry_run").unwrap_or(false);
+        let check_answers = config.flag("check_answers").unwrap_or(true);
+        let wait_for_unlock = config.flag("wait_for_unlock").unwrap_or(false);
+        let confirm = config.flag("confirm").unwrap_or(true) && std::io::stdin().is_terminal();
 
         Self {
             year,
             day,
             url,
             session_token,
+            user_agent,
             cache,
+            throttle,
+            retries,
+            retry_delay,
+            transport: Box::new(transport),
+            cassette,
             test_file: test_file.map(|s| s.to_string()),
+            test_input: None,
+            expected_answers: [None, None],
+            checked_parts: [AtomicBool::new(false), AtomicBool::new(false)],
+            verify,
+            dry_run,
+            confirm,
+            check_answers,
+            offline: false,
+            wait_for_unlock,
+            clock_offset: AtomicI64::new(0),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Create a client that never contacts Advent of Code, for a puzzle whose input is already cached.
+    ///
+    /// Submitted answers are checked against the cached correct answers instead, and fail the check
+    /// if they differ or no correct answer is known. This is what the tests generated by
+    /// `#[aocd(year, day, tests)]` use.
+    ///
+    /// Returns `None` if no session cookie is found, or the input is not cached.
+    #[must_use]
+    pub fn cached(year: u16, day: u8) -> Option<Self> {
+        try_find_aoc_token()?;
+        let mut client = Self::new(year, day, None);
+        client.cache.get_input().ok()?;
+        client.verify = true;
+        client.offline = true;
+        Some(client)
+    }
+
+    /// The answer the test file should give for the given part.
+    ///
+    /// Submitting an answer then checks it against this, and [`Self::finish`] exits with a
+    /// non-zero status if it differs, or if no answer to the part was submitted at all.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::Aocd;
+    ///
+    /// let client = Aocd::new(2022, 1, Some("test_input.txt")).with_expected_answer(1, 24000);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the part is not 1 or 2.
+    #[must_use]
+    pub fn with_expected_answer<P>(mut self, part: P, answer: impl Display) -> Self
+    where
+        P: TryInto<Part>,
+        P::Error: Display,
+    {
+        let part = u8::from(to_part(part));
+        self.expected_answers[usize::from(part) - 1] = Some(answer.to_string());
+        self
+    }
+
+    /// Use the given text as the content of the test file, instead of reading the file, e.g. to
+    /// embed it in the binary with `include_str!`, as `#[aocd(year, day, "file", embed)]` does.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::Aocd;
+    ///
+    /// let client = Aocd::new(2022, 1, Some("test_input.txt")).with_test_input("1000\n2000");
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the client has no test file.
+    #[must_use]
+    pub fn with_test_input(mut self, input: &str) -> Self {
+        assert!(
+            self.test_file.is_some(),
+            "A test input can only be given along with a test file"
+        );
+        self.test_input = Some(input.to_string());
+        self
+    }
+
+    /// Announce a run of the `#[aocd]` function with this client's input, after runs with the
+    /// `previous` clients. Returns false if this is the real input and an example failed, in which
+    /// case it should not be run.
+    #[doc(hidden)]
+    pub fn begin(&self, previous: &[Aocd]) -> bool {
+        if !previous.is_empty() {
+            println!();
+        }
+        match &self.test_file {
+            Some(test_file) => {
+                // Files in the current directory, as from `#[aocd]`, are shown relative to it.
+                let path = Path::new(test_file);
+                let path = std::env::current_dir()
+                    .ok()
+                    .and_then(|dir| path.strip_prefix(dir).ok())
+                    .unwrap_or(path);
+                println!("🧪 Example {} 🧪", path.display());
+            }
+            None if previous.iter().any(Aocd::failed) => {
+                println!("⏭️ Skipping the puzzle input, since an example failed ⏭️");
+                return false;
+            }
+            None => println!("🎄 Puzzle input 🎄"),
+        }
+        true
+    }
+
+    /// Report that a run of the `#[aocd]` function with this client's input returned an error, and
+    /// count it as failed, so that the puzzle input is skipped and [`Self::finish`] exits with a
+    /// non-zero status.
+    #[doc(hidden)]
+    pub fn fail_run(&self, error: &str) {
+        self.failed.store(true, Ordering::Relaxed);
+        println!("❌ Run failed with an error ❌\n{error}");
+    }
+
+    /// Check submitted answers against the correct answers cached when the puzzle was solved, instead
+    /// of submitting them, e.g. to make sure a refactored solution still works.
+    ///
+    /// Each part is reported as passed (✔) or failed (✘), and fails if the answer differs or no
+    /// correct answer is known. [`Self::finish`] then exits with a non-zero status.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::Aocd;
+    ///
+    /// let client = Aocd::new(2022, 1, None).with_verify(true);
+    /// client.submit(1, 24000);
+    /// client.finish();
+    /// ```
+    #[must_use]
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Don't submit answers, but print them along with whether they match a known correct or wrong
+    /// answer. The real input is still used, unlike with a test file.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::Aocd;
+    ///
+    /// let client = Aocd::new(2022, 1, None).with_dry_run(true);
+    /// client.submit(1, 24000);
+    /// ```
+    #[must_use]
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Whether to refuse submitting answers that look like mistakes: empty answers, answers with
+    /// whitespace, Debug-formatted values like `Some(42)` or `"42"`, `0`, and part 2 answers equal to
+    /// the answer to part 1. On by default.
+    ///
+    /// Previous wrong guesses are never submitted again, regardless of this.
+    #[must_use]
+    pub fn with_answer_checks(mut self, check_answers: bool) -> Self {
+        self.check_answers = check_answers;
+        self
+    }
+
+    /// Whether [`Self::get_input`] should wait for the puzzle to unlock, rather than fail, when
+    /// called too early. It shows a countdown while waiting, and fetches the input as soon as the
+    /// puzzle unlocks, going by the clock of the Advent of Code server.
+    #[must_use]
+    pub fn with_wait_for_unlock(mut self, wait_for_unlock: bool) -> Self {
+        self.wait_for_unlock = wait_for_unlock;
+        self
+    }
+
+    /// Whether any submitted answer failed its check, or an expected answer was never checked
+    /// because no answer to that part was submitted.
+    #[must_use]
+    pub fn failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed) || !self.unchecked_parts().is_empty()
+    }
+
+    /// Exit the process with a non-zero status if any submitted answer failed its check, or an
+    /// expected answer was never checked.
+    ///
+    /// The `#[aocd]` attribute calls this when the annotated function returns.
+    pub fn finish(&self) {
+        for part in self.unchecked_parts() {
+            println!(
+                "❌ Part {part} test failed: an answer was expected, but none was submitted ❌"
+            );
+        }
+        if self.failed() {
+            std::process::exit(1);
         }
     }
 
+    /// The parts with an expected answer that no submitted answer has been checked against.
+    fn unchecked_parts(&self) -> Vec<u8> {
+        (1..=2)
+            .filter(|&part| {
+                let i = usize::from(part) - 1;
+                self.expected_answers[i].is_some() && !self.checked_parts[i].load(Ordering::Relaxed)
+            })
+            .collect()
+    }
+
+    /// Call [`Self::finish`] when the returned guard is dropped, unless panicking. This is how the
+    /// `#[aocd]` attribute finishes when the annotated function returns, however it returns.
+    #[doc(hidden)]
+    pub fn finish_on_drop(&self) -> FinishOnDrop<'_> {
+        FinishOnDrop(self)
+    }
+
+    /// Use the given transport for all requests to Advent of Code, instead of the default
+    /// [`MinreqTransport`]. Retries and throttling are still handled by the client.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use aocd::http::{Request, Response};
+    /// use aocd::Aocd;
+    ///
+    /// let client = Aocd::new(2020, 1, None).with_transport(|request: &Request| {
+    ///     Ok(Response {
+    ///         status_code: 200,
+    ///         headers: Default::default(),
+    ///         body: format!("fake response to {}", request.url),
+    ///     })
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Box::new(transport);
+        self
+    }
+
     /// Get the puzzle input for the given year and day.
     ///
     /// If possible this will fetch from a local cache, and only fall back to the server if necessary.
     ///
+    /// If the puzzle isn't unlocked yet, this waits for it with the `wait_for_unlock` setting on.
+    ///
     /// # Panics
-    /// Panics if the Advent of Code server responds with an error.
+    /// Panics if the input can't be fetched, e.g. because the puzzle isn't unlocked yet, or the
+    /// Advent of Code server responds with an error. See [`Self::try_get_input`].
     #[must_use]
     pub fn get_input(&self) -> String {
-        if let Some(test_file) = &self.test_file {
-            return std::fs::read_to_string(test_file)
-                .expect("Failed to read test file")
-                .trim_end_matches('\n')
-                .trim_end_matches('\r')
-                .to_string();
+        match self.try_get_input() {
+            Ok(input) => input,
+            Err(e) if self.wait_for_unlock && e.is::<NotYetUnlocked>() => {
+                if let Ok(response) = self.send(self.clock_request()) {
+                    self.sync_clock(&response);
+                }
+                while let Some(remaining) = self.until_unlock() {
+                    self.show_countdown(remaining);
+                    std::thread::sleep(remaining.min(Duration::from_secs(1)));
+                }
+                println!();
+
+                // The input may take a moment to be served once the puzzle unlocks.
+                let mut attempt = 0;
+                loop {
+                    match self.try_get_input() {
+                        Ok(input) => return input,
+                        Err(_) if attempt < UNLOCK_RETRIES => {
+                            std::thread::sleep(UNLOCK_RETRY_DELAY)
+                        }
+                        Err(e) => panic!("Failed to get input: {e:#}"),
+                    }
+                    attempt += 1;
+                }
+            }
+            Err(e) => panic!("Failed to get input: {e:#}"),
         }
+    }
 
-        if let Ok(input) = self.cache.get_input() {
-            return input;
+    /// Get the puzzle input for the given year and day, like [`Self::get_input`], but return an
+    /// error instead of panicking.
+    ///
+    /// # Errors
+    /// Returns a [`NotYetUnlocked`] error if the puzzle isn't unlocked yet, and other errors if the
+    /// input can't be fetched.
+    pub fn try_get_input(&self) -> Result<String> {
+        if let Some(input) = self.local_input() {
+            return Ok(input);
         }
 
-        let input = minreq::get(format!("{}/{}/day/{}/input", self.url, self.year, self.day))
-            .with_header("Cookie", format!("session={}", self.session_token))
+        self.check_unlocked()?;
+        let response = self.send(self.input_request())?;
+        self.store_input(&response)
+    }
+
+    /// Get the puzzle description, along with the known answers and collected stars.
+    ///
+    /// The puzzle page is cached, and fetched again after a part is solved so that the description
+    /// of part 2 shows up once it's unlocked.
+    ///
+    /// # Panics
+    /// Panics if the Advent of Code server responds with an error, or the page can't be parsed.
+    #[must_use]
+    pub fn puzzle(&self) -> Puzzle {
+        if let Some(puzzle) = self.local_puzzle() {
+            return puzzle;
+        }
+
+        let response = self
+            .send(self.puzzle_page_request())
+            .expect("Failed to get puzzle page");
+        self.store_puzzle(&response)
+            .expect("Failed to parse puzzle page")
+    }
+
+    /// Submit an answer to the given year, day, and part.
+    ///
+    /// The answer can be anything implementing [`AocAnswer`], like a number, a string, or an
+    /// `Option` of one, which is only submitted if it is `Some`.
+    ///
+    /// The last day of an event (day 25, or day 12 from 2025 on) has no answer for part 2, so
+    /// nothing is submitted for it.
+    ///
+    /// # Panics
+    /// Panics if the part is not 1 or 2, or the Advent of Code server responds to the submission
+    /// with an error.
+    pub fn submit<P>(&self, part: P, answer: impl AocAnswer)
+    where
+        P: TryInto<Part>,
+        P::Error: Display,
+    {
+        let part = u8::from(to_part(part));
+        let Some(answer) = self.answer_string(part, &answer) else {
+            return;
+        };
+
+        let mut looked_up_past_answers = false;
+        while let Some(request) = self.answer_request(part, &answer) {
+            let response = self.send(request).expect("Faled to submit answer");
+            if self.handle_answer_response(part, &answer, &response) == AnswerOutcome::Done {
+                return;
+            }
+
+            // We've apparently already solved this in the past, but the cache has no memory of that.
+            // In this case we look up what we've solved in the past, and cache it.
+            // Then we can restart the submit flow entirely, and it should not hit this case again.
+            self.announce_past_answers_lookup();
+            let cached = self
+                .send(self.puzzle_page_request())
+                .and_then(|page| self.cache_past_answers(&page));
+            assert!(
+                cached.is_ok() && !looked_up_past_answers,
+                "Failed to cache past answers, even though we thought we had solved this puzzle before. BUG!"
+            );
+            looked_up_past_answers = true;
+        }
+    }
+
+    /// The stars of the year that are not known to be collected, as days and parts, going by the
+    /// correct answers aocd has cached. The final star, part 2 of the last day, is not included.
+    ///
+    /// Only the cache is looked at, so stars collected without aocd are reported as missing, until
+    /// aocd learns their answers, e.g. when they are submitted again with aocd.
+    ///
+    /// # Panics
+    /// Panics if the cache could not be read.
+    #[must_use]
+    pub fn missing_stars(&self) -> Vec<(u8, Part)> {
+        let mut missing = Vec::new();
+        let last_day = calendar::last_day(self.year);
+        for day in 1..=last_day {
+            let cache = cache::Cache::new(self.year, day, &self.session_token)
+                .expect("Should be able to create cache for aocd");
+            for part in [Part::One, Part::Two] {
+                if (day, part) != (last_day, Part::Two)
+                    && cache.get_correct_answer(part.into()).is_err()
+                {
+                    missing.push((day, part));
+                }
+            }
+        }
+        missing
+    }
+
+    /// Claim the final star of the year, which is given on the last day once all the other stars are
+    /// collected, instead of for an answer to part 2.
+    ///
+    /// Nothing is sent if any of the other stars is missing, going by [`Self::missing_stars`], which
+    /// only knows of the answers aocd has cached.
+    ///
+    /// # Panics
+    /// Panics if the client is not for the last day, or the Advent of Code server responds with an error.
+    pub fn claim_final_star(&self) {
+        if let Some(request) = self.final_star_request() {
+            let response = self.send(request).expect("Failed to claim the final star");
+            self.handle_answer_response(2, FINAL_STAR_ANSWER, &response);
+        }
+    }
+
+    /// The input from the test file or the cache, if available.
+    pub(crate) fn local_input(&self) -> Option<String> {
+        if let Some(test_file) = &self.test_file {
+            let input = match &self.test_input {
+                Some(input) => input.clone(),
+                None => std::fs::read_to_string(test_file).expect("Failed to read test file"),
+            };
+            return Some(
+                input
+                    .trim_end_matches('\n')
+                    .trim_end_matches('\r')
+                    .to_string(),
+            );
+        }
+
+        self.cache.get_input().ok()
+    }
+
+    pub(crate) fn input_request(&self) -> Request {
+        Request::get(format!("{}/{}/day/{}/input", self.url, self.year, self.day))
             .with_header("Content-Type", "text/plain")
-            .send()
-            .expect("Failed to get input")
-            .as_str()
-            .expect("Failed to parse input as string")
+    }
+
+    /// Cache the input from a response to [`Self::input_request`], and return it.
+    pub(crate) fn store_input(&self, response: &Response) -> Result<String> {
+        if response.status_code != 200 {
+            return Err(anyhow!(
+                "Non 200 response ({}) from AoC when getting input. Check your token.",
+                response.status_code
+            ));
+        }
+        let input = response
+            .body
             .trim_end_matches('\n')
             .trim_end_matches('\r')
             .to_string();
-        self.cache
-            .cache_input(&input)
-            .expect("Should be able to cache input");
-        input
+        self.cache.cache_input(&input)?;
+        Ok(input)
     }
 
-    /// Submit an answer to the given year, day, and part.
-    ///
-    /// # Panics
-    /// Panics if the Advent of Code server responds to the submission with an error.
-    pub fn submit(&self, part: u8, answer: impl Display) {
-        let answer = answer.to_string();
+    /// A [`NotYetUnlocked`] error if the puzzle isn't unlocked yet.
+    pub(crate) fn check_unlocked(&self) -> Result<()> {
+        match self.until_unlock() {
+            Some(_) => Err(NotYetUnlocked {
+                year: self.year,
+                day: self.day,
+                unlocks_at: calendar::unlock_time(self.year, self.day),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
 
-        if self.test_file.is_some() {
-            println!("🕵️ Part {part} test result: {answer} 🕵️");
+    /// The time left until the puzzle unlocks, going by the server's clock if known.
+    pub(crate) fn until_unlock(&self) -> Option<Duration> {
+        let offset = self.clock_offset.load(Ordering::Relaxed);
+        let now = SystemTime::now();
+        let server_now = if offset >= 0 {
+            now + Duration::from_millis(offset.unsigned_abs())
+        } else {
+            now - Duration::from_millis(offset.unsigned_abs())
+        };
+        calendar::unlock_time(self.year, self.day)
+            .duration_since(server_now)
+            .ok()
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// A request for a page that is always available, to learn the server's time from.
+    pub(crate) fn clock_request(&self) -> Request {
+        Request::get(format!("{}/{}", self.url, self.year))
+    }
+
+    /// Learn how far off our clock is from the `Date` of a response.
+    pub(crate) fn sync_clock(&self, response: &Response) {
+        let Some(server_time) = response
+            .headers
+            .get("date")
+            .and_then(|d| parse_http_date(d))
+        else {
             return;
+        };
+        let now = SystemTime::now();
+        let offset = match server_time.duration_since(now) {
+            Ok(ahead) => i64::try_from(ahead.as_millis()).unwrap_or(i64::MAX),
+            Err(behind) => -i64::try_from(behind.duration().as_millis()).unwrap_or(i64::MAX),
+        };
+        self.clock_offset.store(offset, Ordering::Relaxed);
+    }
+
+    pub(crate) fn show_countdown(&self, remaining: Duration) {
+        print!(
+            "\r⏳ {} day {} unlocks in {} ⏳",
+            self.year,
+            self.day,
+            format_countdown(remaining)
+        );
+        let _ = std::io::stdout().flush();
+    }
+
+    /// The puzzle from the cached puzzle page, if available.
+    pub(crate) fn local_puzzle(&self) -> Option<Puzzle> {
+        let html = self.cache.get_puzzle_page().ok()?;
+        Puzzle::parse(self.year, self.day, &html).ok()
+    }
+
+    /// Cache the page from a response to [`Self::puzzle_page_request`], and return the puzzle.
+    pub(crate) fn store_puzzle(&self, response: &Response) -> Result<Puzzle> {
+        if response.status_code != 200 {
+            return Err(anyhow!(
+                "Non 200 response from AoC when getting puzzle page. Check your token."
+            ));
+        }
+        let puzzle = Puzzle::parse(self.year, self.day, &response.body)?;
+        self.cache.cache_puzzle_page(&response.body)?;
+        Ok(puzzle)
+    }
+
+    /// Report whether the final star can be claimed, and what's missing if not.
+    fn report_final_star(&self) {
+        let missing = self.missing_stars();
+        if self.cache.get_correct_answer(2).is_ok() {
+            println!("⭐ You already have the final star for {} ⭐", self.year);
+        } else if missing.is_empty() {
+            println!("🎄 Day {} has no part 2 to answer, but you have all the other stars, so the final star can be claimed with `claim_final_star` 🎄", self.day);
+        } else {
+            println!("🎄 Day {} has no part 2 to answer. Its star is given once you have all the other stars, and aocd doesn't know of you having these: {} 🎄", self.day, format_stars(&missing));
+        }
+    }
+
+    /// The request claiming the final star, or `None` if it should not be sent.
+    pub(crate) fn final_star_request(&self) -> Option<Request> {
+        assert!(
+            self.day == calendar::last_day(self.year),
+            "The final star is claimed on day {}, not day {}",
+            calendar::last_day(self.year),
+            self.day
+        );
+        if self.test_file.is_some() || self.verify || self.dry_run {
+            println!("🕵️ Not claiming the final star when not submitting answers 🕵️");
+            return None;
+        }
+        if self.cache.get_correct_answer(2).is_ok() {
+            println!("⭐ You already have the final star for {} ⭐", self.year);
+            return None;
+        }
+        let missing = self.missing_stars();
+        if !missing.is_empty() {
+            println!("🎄 Not claiming the final star, since aocd doesn't know of you having these stars: {}. It only knows the answers it has cached, so submit any you found elsewhere with aocd as well 🎄", format_stars(&missing));
+            return None;
+        }
+
+        Some(
+            Request::post(
+                format!("{}/{}/day/{}/answer", self.url, self.year, self.day),
+                format!("level=2&answer={FINAL_STAR_ANSWER}"),
+            )
+            .with_header("Content-Type", "application/x-www-form-urlencoded"),
+        )
+    }
+
+    /// The answer to submit, or `None` (after reporting why) if there is none.
+    pub(crate) fn answer_string(&self, part: u8, answer: &impl AocAnswer) -> Option<String> {
+        match answer.to_answer() {
+            Ok(answer) => Some(answer),
+            Err(reason) => {
+                // There is no answer to check, so that counts as a failed check.
+                if self.verify || (self.test_file.is_some() && self.expected_answer(part).is_some())
+                {
+                    self.failed.store(true, Ordering::Relaxed);
+                    self.mark_checked(part);
+                }
+                println!("⚠️ Part {part} not submitted: {reason} ⚠️");
+                None
+            }
+        }
+    }
+
+    fn expected_answer(&self, part: u8) -> Option<&str> {
+        part.checked_sub(1)
+            .and_then(|i| self.expected_answers.get(usize::from(i)))
+            .and_then(Option::as_deref)
+    }
+
+    fn mark_checked(&self, part: u8) {
+        if let Some(checked) = part
+            .checked_sub(1)
+            .and_then(|i| self.checked_parts.get(usize::from(i)))
+        {
+            checked.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The request submitting the answer, or `None` if there is no need to submit it (e.g. because
+    /// the answer to this part is already known), or the submission isn't confirmed.
+    pub(crate) fn answer_request(&self, part: u8, answer: &str) -> Option<Request> {
+        let request = self.unconfirmed_answer_request(part, answer)?;
+        if self.confirm && !self.confirm_submission(part, answer, &mut std::io::stdin().lock()) {
+            report_unconfirmed(part, answer);
+            return None;
+        }
+        Some(request)
+    }
+
+    /// [`Self::answer_request`], without asking for confirmation.
+    pub(crate) fn unconfirmed_answer_request(&self, part: u8, answer: &str) -> Option<Request> {
+        if self.test_file.is_some() {
+            self.mark_checked(part);
+            match self.expected_answer(part) {
+                None => println!("🕵️ Part {part} test result: {answer} 🕵️"),
+                Some(expected) if expected == answer => {
+                    println!("✅ Part {part} test passed: {answer} ✅");
+                }
+                Some(expected) => {
+                    self.failed.store(true, Ordering::Relaxed);
+                    println!("❌ Part {part} test failed ❌\n{}", diff(expected, answer));
+                }
+            }
+            return None;
+        }
+
+        if part == 2 && !calendar::has_part_two(self.year, self.day) {
+            self.report_final_star();
+            return None;
+        }
+
+        if self.verify {
+            match self.cache.get_correct_answer(part) {
+                Ok(correct_answer) if correct_answer == answer => {
+                    println!("✔ Part {part} is still correct: {answer} ✔");
+                }
+                Ok(correct_answer) => {
+                    self.failed.store(true, Ordering::Relaxed);
+                    println!(
+                        "✘ Part {part} differs from the correct answer ✘\n{}",
+                        diff(&correct_answer, answer)
+                    );
+                }
+                Err(_) => {
+                    self.failed.store(true, Ordering::Relaxed);
+                    println!(
+                        "✘ Part {part} has no known correct answer to check {answer} against ✘"
+                    );
+                }
+            }
+            return None;
+        }
+
+        if self.dry_run {
+            match self.cache.get_correct_answer(part) {
+                Ok(correct_answer) if correct_answer == answer => {
+                    println!("🏜️ Part {part} dry run: {answer} (the correct answer) 🏜️");
+                }
+                Ok(correct_answer) => println!(
+                    "🏜️ Part {part} dry run: {answer} (not the correct answer: {correct_answer}) 🏜️"
+                ),
+                Err(_) if self.cache.get_answer_response(part, answer).is_ok() => {
+                    println!("🏜️ Part {part} dry run: {answer} (already guessed, and wrong) 🏜️");
+                }
+                Err(_) => println!("🏜️ Part {part} dry run: {answer} (not submitted) 🏜️"),
+            }
+            return None;
         }
 
         // First check if we have already cached a _correct_ answer for this puzzle.
@@ -108,41 +816,122 @@ impl Aocd {
             } else {
                 println!("❌ Part {part} already solved with a different answer: {correct_answer} (you submitted: {answer}) ❌");
             }
-            return;
+            return None;
         }
 
         // Now check if we have already checked this particular answer before. If so we know it is wrong.
-        if let Ok(response) = self.cache.get_answer_response(part, &answer) {
+        if let Ok(response) = self.cache.get_answer_response(part, answer) {
             println!( "❌ You've already incorrectly guessed {answer}, and the server responed with: ❌ \n{response}");
-            return;
+            return None;
+        }
+
+        if let Some(problem) = self
+            .answer_problem(part, answer)
+            .filter(|_| self.check_answers)
+        {
+            println!("⚠️ Part {part} not submitted: {answer:?} ⚠️\n{problem}. Set AOC_CHECK_ANSWERS=0 to submit it anyway.");
+            return None;
         }
 
         // Only now do we actually submit the (new) answer.
-        let url = format!("{}/{}/day/{}/answer", self.url, self.year, self.day);
-        let formdata = format!("level={}&answer={}", part, urlencoding::encode(&answer));
-        let r = minreq::post(url)
-            .with_header("Cookie", format!("session={}", self.session_token))
-            .with_header("Content-Type", "application/x-www-form-urlencoded")
-            .with_body(formdata);
-        let response = r.send().expect("Faled to submit answer");
+        let formdata = format!("level={}&answer={}", part, urlencoding::encode(answer));
+        Some(
+            Request::post(
+                format!("{}/{}/day/{}/answer", self.url, self.year, self.day),
+                formdata,
+            )
+            .with_header("Content-Type", "application/x-www-form-urlencoded"),
+        )
+    }
+
+    /// Why the answer is likely a mistake, if it is.
+    fn answer_problem(&self, part: u8, answer: &str) -> Option<String> {
+        let debug_formatted = ["Some(", "Ok(", "Err("]
+            .iter()
+            .any(|prefix| answer.starts_with(prefix) && answer.ends_with(')'))
+            || answer == "None"
+            || (answer.len() >= 2
+                && ((answer.starts_with('"') && answer.ends_with('"'))
+                    || (answer.starts_with('\'') && answer.ends_with('\''))));
 
+        if answer.trim().is_empty() {
+            Some("The answer is empty".to_string())
+        } else if answer.contains(char::is_whitespace) {
+            Some("The answer contains whitespace or newlines".to_string())
+        } else if debug_formatted {
+            Some("The answer looks Debug-formatted, rather than the value itself".to_string())
+        } else if answer == "0" {
+            Some("The answer is 0, which is rarely right".to_string())
+        } else if part == 2 && self.cache.get_correct_answer(1).is_ok_and(|a| a == answer) {
+            Some("The answer is the same as the answer to part 1".to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Show what is known about the part, and ask whether to submit the answer. Only "y" or "yes"
+    /// read from `input` is a yes.
+    fn confirm_submission(&self, part: u8, answer: &str, input: &mut impl BufRead) -> bool {
+        self.ask_confirmation(part, answer);
+        read_confirmation(input)
+    }
+
+    /// Show what is known about the part, and ask whether to submit the answer.
+    pub(crate) fn ask_confirmation(&self, part: u8, answer: &str) {
+        print!("{}", self.previous_guesses(part));
+        print!("Submit {answer} as the answer to part {part}? [y/N] ");
+        let _ = std::io::stdout().flush();
+    }
+
+    /// A summary of the wrong answers given for the part, and the bounds they imply.
+    fn previous_guesses(&self, part: u8) -> String {
+        let guesses = self.cache.wrong_answers(part).unwrap_or_default();
+        if guesses.is_empty() {
+            return String::new();
+        }
+
+        let mut lower = None;
+        let mut upper = None;
+        let mut summary = format!("Previous guesses for part {part}:\n");
+        for (guess, response) in &guesses {
+            let hint = if response.contains("too high") {
+                upper = guess.parse::<i128>().ok().into_iter().chain(upper).min();
+                " (too high)"
+            } else if response.contains("too low") {
+                lower = guess.parse::<i128>().ok().into_iter().chain(lower).max();
+                " (too low)"
+            } else {
+                ""
+            };
+            summary += &format!("  {guess}{hint}\n");
+        }
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => {
+                summary += &format!("The answer is above {lower} and below {upper}\n");
+            }
+            (Some(lower), None) => summary += &format!("The answer is above {lower}\n"),
+            (None, Some(upper)) => summary += &format!("The answer is below {upper}\n"),
+            (None, None) => {}
+        }
+        summary
+    }
+
+    /// Report and cache the response to an [`Self::answer_request`].
+    pub(crate) fn handle_answer_response(
+        &self,
+        part: u8,
+        answer: &str,
+        response: &Response,
+    ) -> AnswerOutcome {
         assert!(
             response.status_code == 200,
             "Non 200 response from AoC when posting answer. Failed to submit answer. Check your token."
         );
-        let response_html = response
-            .as_str()
-            .expect("Falied to read response from AoC after posting answer.");
-
-        self.handle_answer_response(part, &answer, response_html)
-            .expect("Failed to handle response from AoC");
-    }
 
-    fn handle_answer_response(&self, part: u8, answer: &str, html: &str) -> Result<()> {
-        let mut response = None;
-        for line in html.lines() {
+        let mut message = None;
+        for line in response.body.lines() {
             if line.starts_with("<article>") {
-                response = Some(
+                message = Some(
                     line.trim_start_matches("<article>")
                         .trim_end_matches("</article>")
                         .trim_start_matches("<p>")
@@ -150,59 +939,55 @@ impl Aocd {
                 );
             }
         }
-        let response = response.expect("Failed to parse response from AoC when submitting answer.");
+        let message = message.expect("Failed to parse response from AoC when submitting answer.");
 
-        if response.contains("That's the right answer!") {
+        let cached = if message.contains("That's the right answer!") {
             println!("🌟 Part {part} correctly solved with answer: {answer} 🌟");
+            // The puzzle page now has a new part (or at least a new answer) to show.
+            self.cache.clear_puzzle_page().and_then(|()| {
+                self.cache
+                    .cache_answer_response(part, answer, message, true)
+            })
+        } else if message.contains("Congratulations!") {
+            println!("🎄 {message} 🎄");
+            self.cache.clear_puzzle_page().and_then(|()| {
+                self.cache
+                    .cache_answer_response(part, answer, message, true)
+            })
+        } else if message.contains("That's not the right answer") {
+            println!("❌ {message}");
             self.cache
-                .cache_answer_response(part, answer, response, true)?;
-        } else if response.contains("That's not the right answer") {
-            println!("❌ {response}");
-            self.cache
-                .cache_answer_response(part, answer, response, false)?;
-        } else if response.contains("You gave an answer too recently") {
+                .cache_answer_response(part, answer, message, false)
+        } else if message.contains("You gave an answer too recently") {
             // Don't cache this response.
-            println!("❌ {response}");
-        } else if response.contains("Did you already complete it") {
-            // We've apparently already solved this in the past, but the cache has no memory of that.
-            // In this case we look up what we've solved in the past, and cache it.
-            // Then we can restart the submit flow entirely, and it should not hit this case again.
-            match self.cache_past_answers() {
-                Ok(()) => self.submit(part, answer),
-                _ => panic!("Failed to cache past answers, even though we thought we had solved this puzzle before. BUG!"),
-            }
-        }
-        Ok(())
+            println!("❌ {message}");
+            Ok(())
+        } else if message.contains("Did you already complete it") {
+            return AnswerOutcome::AlreadyCompleted;
+        } else {
+            Ok(())
+        };
+        cached.expect("Failed to handle response from AoC");
+        AnswerOutcome::Done
     }
 
-    fn cache_past_answers(&self) -> Result<()> {
+    pub(crate) fn announce_past_answers_lookup(&self) {
         println!("You appear to have answered this puzzle before, but aocd doesn't remember that.");
         println!(
             "Caching past answers for {} day {} by parsing the puzzle page.",
             self.year, self.day
         );
-        let url = format!("{}/{}/day/{}/answer", self.url, self.year, self.day);
-        let response = minreq::get(url)
-            .with_header("Cookie", format!("session={}", self.session_token))
+    }
+
+    pub(crate) fn puzzle_page_request(&self) -> Request {
+        Request::get(format!("{}/{}/day/{}", self.url, self.year, self.day))
             .with_header("Content-Type", "text/plain")
-            .send()?;
-        if response.status_code != 200 {
-            return Err(anyhow!(
-                "Non 200 response from AoC when getting puzzle page. Failed to cache past answers. Check your token."
-            ));
-        }
-        let response_html = response.as_str()?;
+    }
 
-        let mut part1: Option<String> = None;
-        let mut part2: Option<String> = None;
-        let re = Regex::new(r#"Your puzzle answer was <code>(.*?)</code>"#).unwrap();
-        for capture in re.captures_iter(response_html) {
-            if part1.is_none() {
-                part1 = Some(capture[1].to_string());
-            } else {
-                part2 = Some(capture[1].to_string());
-            }
-        }
+    /// Cache the answers found in a response to [`Self::puzzle_page_request`].
+    pub(crate) fn cache_past_answers(&self, response: &Response) -> Result<()> {
+        let puzzle = self.store_puzzle(response)?;
+        let [part1, part2] = puzzle.answers;
         println!("Found past answers: {part1:?} {part2:?}");
         let mut found_any = false;
         if let Some(part1) = part1 {
@@ -221,28 +1006,259 @@ impl Aocd {
             Err(anyhow!("Failed to find past answers"))
         }
     }
+
+    /// Add the session cookie and User-Agent to the request, and replay a recorded response for it
+    /// if replaying.
+    pub(crate) fn prepare(&self, request: Request) -> Result<(Request, Option<Response>)> {
+        if self.offline {
+            return Err(anyhow!(
+                "The client is offline, so it can't request {}",
+                request.url
+            ));
+        }
+        let request = request
+            .with_header("Cookie", format!("session={}", self.session_token))
+            .with_header("User-Agent", &self.user_agent);
+        let replayed = match &self.cassette {
+            Some(cassette) => cassette.replay(&self.url, &request)?,
+            None => None,
+        };
+        Ok((request, replayed))
+    }
+
+    /// Record the response to the request if recording.
+    pub(crate) fn record(&self, request: &Request, response: &Response) -> Result<()> {
+        match &self.cassette {
+            Some(cassette) => cassette.record(&self.url, request, response),
+            None => Ok(()),
+        }
+    }
+
+    /// How long to wait before retrying the request, if it should be retried after this attempt.
+    ///
+    /// Only GET requests are retried, as submitting an answer twice could cost a wrong-answer
+    /// timeout.
+    pub(crate) fn retry_delay(
+        &self,
+        request: &Request,
+        attempt: u32,
+        result: &Result<Response>,
+    ) -> Option<Duration> {
+        let failed = match result {
+            Ok(response) => response.status_code >= 500,
+            Err(_) => true,
+        };
+        if !failed || request.method != Method::Get || attempt >= self.retries {
+            return None;
+        }
+
+        let delay = self.retry_delay * 2_u32.saturating_pow(attempt);
+        println!(
+            "⚠️ Request to {} failed, retrying in {:.1}s ⚠️",
+            request.url,
+            delay.as_secs_f64()
+        );
+        Some(delay)
+    }
+
+    fn send(&self, request: Request) -> Result<Response> {
+        let (request, replayed) = self.prepare(request)?;
+        if let Some(response) = replayed {
+            return Ok(response);
+        }
+        let mut attempt = 0;
+        loop {
+            std::thread::sleep(self.throttle.reserve());
+            let result = self.transport.send(&request);
+            match self.retry_delay(&request, attempt, &result) {
+                Some(delay) => std::thread::sleep(delay),
+                None => {
+                    let response = result?;
+                    self.record(&request, &response)?;
+                    return Ok(response);
+                }
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// What to do after handling the response to an answer submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnswerOutcome {
+    Done,
+    /// The server says the part is already solved, but the cache didn't know. The past answers
+    /// should be cached from the puzzle page, and the submission retried.
+    AlreadyCompleted,
+}
+
+/// The stars as a list like "day 1 part 2, day 3 part 1".
+fn format_stars(stars: &[(u8, Part)]) -> String {
+    stars
+        .iter()
+        .map(|(day, part)| format!("day {day} part {part}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether the reply to [`Aocd::ask_confirmation`] read from `input` is "y" or "yes".
+pub(crate) fn read_confirmation(input: &mut impl BufRead) -> bool {
+    let mut reply = String::new();
+    input.read_line(&mut reply).is_ok()
+        && matches!(reply.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Report that the answer was not submitted, since that wasn't confirmed.
+pub(crate) fn report_unconfirmed(part: u8, answer: &str) {
+    println!("🛑 Part {part} not submitted: {answer} 🛑");
+}
+
+/// Calls [`Aocd::finish`] when dropped, see [`Aocd::finish_on_drop`].
+#[doc(hidden)]
+#[must_use]
+pub struct FinishOnDrop<'a>(&'a Aocd);
+
+impl Drop for FinishOnDrop<'_> {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.0.finish();
+        }
+    }
+}
+
+/// The error for getting the input of a puzzle before it unlocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotYetUnlocked {
+    pub year: u16,
+    pub day: u8,
+    pub unlocks_at: SystemTime,
+}
+
+impl Display for NotYetUnlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let remaining = self
+            .unlocks_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} day {} isn't unlocked yet. It unlocks at midnight EST, in {}",
+            self.year,
+            self.day,
+            format_countdown(remaining)
+        )
+    }
+}
+
+impl std::error::Error for NotYetUnlocked {}
+
+/// How many more times to try getting the input after waiting for the puzzle to unlock.
+pub(crate) const UNLOCK_RETRIES: u32 = 5;
+pub(crate) const UNLOCK_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Format a duration like `1d 02:03:04`.
+fn format_countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if days > 0 {
+        format!("{days}d {time}")
+    } else {
+        time
+    }
+}
+
+/// What is sent as the "answer" when claiming the final star, like the form on the last day does.
+pub(crate) const FINAL_STAR_ANSWER: &str = "0";
+
+/// Show the expected and actual answer below each other, marking where they start to differ.
+fn diff(expected: &str, actual: &str) -> String {
+    let common = expected
+        .chars()
+        .zip(actual.chars())
+        .take_while(|(e, a)| e == a)
+        .count();
+    format!(
+        "  expected: {expected}\n  actual:   {actual}\n            {}^",
+        " ".repeat(common)
+    )
+}
+
+/// The default transport, with the timeout from the config.
+pub(crate) fn default_transport(config: &Config) -> MinreqTransport {
+    MinreqTransport::default().with_timeout(
+        config
+            .duration("timeout")
+            .unwrap_or(Duration::from_secs(30)),
+    )
+}
+
+fn default_user_agent(contact: Option<&str>) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    match contact {
+        Some(contact) => format!("aocd/{version} (github.com/bsamseth/aocd by {contact})"),
+        None => format!("aocd/{version} (github.com/bsamseth/aocd)"),
+    }
 }
 
 fn find_aoc_token() -> String {
+    try_find_aoc_token().unwrap_or_else(|| {
+        panic!("No AoC session token found. See https://crates.io/crates/aocd for how to set it.",)
+    })
+}
+
+fn try_find_aoc_token() -> Option<String> {
     if let Ok(session) = std::env::var("AOC_SESSION").or_else(|_| std::env::var("AOC_TOKEN")) {
-        return session.trim().to_string();
+        return Some(session.trim().to_string());
     }
 
     let token_path = std::env::var("AOC_TOKEN_PATH")
         .unwrap_or_else(|_| shellexpand::tilde("~/.config/aocd/token").to_string());
     std::fs::read_to_string(token_path)
-        .unwrap_or_else(|_| {
-            panic!(
-                "No AoC session token found. See https://crates.io/crates/aocd for how to set it.",
-            )
-        })
-        .trim()
-        .to_string()
+        .ok()
+        .map(|token| token.trim().to_string())
+}
+
+/// Environment variables for tests, isolating them from the settings of whoever runs them, with the
+/// cache and config in `dir`, and the given variables set on top.
+#[cfg(test)]
+pub(crate) fn test_env<V: AsRef<str>>(
+    dir: &Path,
+    vars: &[(&'static str, V)],
+) -> Vec<(&'static str, Option<String>)> {
+    let mut env = vec![
+        ("AOC_SESSION", Some("test-session".to_string())),
+        ("AOC_CACHE_DIR", Some(dir.to_str().unwrap().to_string())),
+        (
+            "AOC_CONFIG_PATH",
+            Some(dir.join("config").to_str().unwrap().to_string()),
+        ),
+        ("AOC_URL", None),
+        ("AOC_THROTTLE", Some("0".to_string())),
+        ("AOC_RECORD", None),
+        ("AOC_REPLAY", None),
+        ("AOC_VERIFY", None),
+        ("AOC_DRY_RUN", None),
+        ("AOC_CONFIRM", Some("0".to_string())),
+        ("AOC_CHECK_ANSWERS", None),
+        ("AOC_WAIT_FOR_UNLOCK", None),
+    ];
+    for (name, value) in vars {
+        env.retain(|(n, _)| n != name);
+        env.push((name, Some(value.as_ref().to_string())));
+    }
+    env
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{FakePuzzle, FakeServer};
     use mockito::mock;
     use std::fs::File;
     use std::io::Write;
@@ -252,6 +1268,7 @@ mod tests {
         year: u16,
         day: u8,
         input: Option<String>,
+        vars: Vec<(&'static str, String)>,
     }
 
     impl TestClientBuilder {
@@ -260,6 +1277,7 @@ mod tests {
                 year: 2015,
                 day: 1,
                 input: None,
+                vars: Vec::new(),
             }
         }
         fn year(mut self, year: u16) -> Self {
@@ -274,6 +1292,13 @@ mod tests {
             self.input = Some(input.to_string());
             self
         }
+        fn var(mut self, name: &'static str, value: &str) -> Self {
+            self.vars.push((name, value.to_string()));
+            self
+        }
+        fn url(self, url: &str) -> Self {
+            self.var("AOC_URL", url)
+        }
         fn run<F, T>(&self, test: F) -> Result<T>
         where
             T: std::panic::RefUnwindSafe,
@@ -284,30 +1309,25 @@ mod tests {
         {
             let cache_path = std::env::temp_dir().join("aocd-tests");
             let _ignore = std::fs::remove_dir_all(&cache_path);
+            let vars = test_env(&cache_path, &self.vars);
 
-            temp_env::with_vars(
-                vec![
-                    ("AOC_SESSION", Some("test-session")),
-                    ("AOC_CACHE_DIR", Some(cache_path.to_str().unwrap())),
-                ],
-                move || {
-                    let client = Aocd::new(self.year, self.day, None);
-                    if let Some(input) = &self.input {
-                        let url = format!("/{}/day/{}/input", client.year, client.day);
-                        let m = mock("GET", url.as_str())
-                            .with_status(200)
-                            .with_header("Content-Type", "text/plain")
-                            .with_body(input)
-                            .expect(1)
-                            .create();
-                        let result = test(&client);
-                        m.assert();
-                        result
-                    } else {
-                        test(&client)
-                    }
-                },
-            )
+            temp_env::with_vars(vars, move || {
+                let client = Aocd::new(self.year, self.day, None);
+                if let Some(input) = &self.input {
+                    let url = format!("/{}/day/{}/input", client.year, client.day);
+                    let m = mock("GET", url.as_str())
+                        .with_status(200)
+                        .with_header("Content-Type", "text/plain")
+                        .with_body(input)
+                        .expect(1)
+                        .create();
+                    let result = test(&client);
+                    m.assert();
+                    result
+                } else {
+                    test(&client)
+                }
+            })
         }
     }
 
@@ -337,9 +1357,575 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
-    fn test_submit_answer() {
-        todo!()
+    fn test_not_yet_unlocked() -> Result<()> {
+        TestClientBuilder::new().year(2099).day(1).run(|client| {
+            let error = client.try_get_input().unwrap_err();
+            let error = error.downcast_ref::<NotYetUnlocked>().unwrap();
+            assert_eq!(error.unlocks_at, calendar::unlock_time(2099, 1));
+            assert!(error
+                .to_string()
+                .starts_with("2099 day 1 isn't unlocked yet"));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_error_page_not_cached() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 1).input("1\n2"));
+        server.set_logged_in(false);
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .run(|client| {
+                assert!(client.try_get_input().is_err());
+                assert!(client.cache.get_input().is_err());
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_wait_for_unlock() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 1).input("1\n2"));
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .var("AOC_WAIT_FOR_UNLOCK", "1")
+            .run(|client| {
+                client.sync_clock(&Response {
+                    status_code: 200,
+                    headers: [(
+                        "date".to_string(),
+                        "Thu, 01 Dec 2022 04:59:50 GMT".to_string(),
+                    )]
+                    .into(),
+                    body: String::new(),
+                });
+                let remaining = client.until_unlock().unwrap();
+                assert!(remaining > Duration::from_secs(9) && remaining <= Duration::from_secs(10));
+                assert!(client.try_get_input().unwrap_err().is::<NotYetUnlocked>());
+
+                // Pretend the server's clock is a second before the unlock.
+                client.clock_offset.fetch_add(9_000, Ordering::Relaxed);
+                let start = std::time::Instant::now();
+                assert_eq!(client.get_input(), "1\n2");
+                assert!(start.elapsed() >= Duration::from_millis(500));
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(Duration::from_millis(61_500)), "00:01:02");
+        assert_eq!(format_countdown(Duration::from_secs(90_061)), "1d 01:01:01");
+    }
+
+    #[test]
+    fn test_submit_answer() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(
+            FakePuzzle::new(2022, 1)
+                .answer(1, "24000")
+                .answer(2, "45000"),
+        );
+
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .run(|client| {
+                client.submit(1, 123);
+                // Known wrong answers and known correct answers are not submitted again.
+                client.submit(1, 123);
+                client.submit(1, 24000);
+                client.submit(1, 24000);
+                client.submit(2, 45000);
+                assert_eq!(client.cache.get_correct_answer(1)?, "24000");
+                assert_eq!(client.cache.get_correct_answer(2)?, "45000");
+                Ok(())
+            })?;
+
+        let answers: Vec<_> = server
+            .submissions()
+            .into_iter()
+            .map(|s| (s.part, s.answer))
+            .collect();
+        assert_eq!(
+            answers,
+            vec![
+                (1, "123".to_string()),
+                (1, "24000".to_string()),
+                (2, "45000".to_string())
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_submit_answer_solved_before() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 1).answer(1, "24000").solved(1));
+
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .run(|client| {
+                // The server says the part is already complete, so the past answer is looked up
+                // on the puzzle page instead.
+                client.submit(1, 24000);
+                assert_eq!(client.cache.get_correct_answer(1)?, "24000");
+                Ok(())
+            })?;
+
+        assert_eq!(server.submissions().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expected_answers() -> Result<()> {
+        TestClientBuilder::new().run(|_| {
+            let client = Aocd::new(2022, 1, Some("tests/inputs/2022-01.txt"))
+                .with_expected_answer(1, 24000)
+                .with_expected_answer(2, "45000");
+            assert!(client.input_request().url.ends_with("/2022/day/1/input"));
+            assert_eq!(client.get_input().lines().count(), 14);
+
+            client.submit(1, 24000);
+            assert!(!client.failed.load(Ordering::Relaxed));
+            client.submit(2, 45001);
+            assert!(client.failed.load(Ordering::Relaxed));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_unchecked_expected_answer_fails() -> Result<()> {
+        TestClientBuilder::new().run(|_| {
+            let example =
+                Aocd::new(2022, 1, Some("tests/inputs/2022-01.txt")).with_expected_answer(2, 45000);
+            let real = Aocd::new(2022, 1, None);
+            assert!(example.begin(&[]));
+            example.submit(1, 24000);
+            assert!(example.failed());
+            assert_eq!(example.unchecked_parts(), [2]);
+            assert!(!real.begin(std::slice::from_ref(&example)));
+
+            example.submit(2, 45000);
+            assert!(!example.failed());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_embedded_test_input() -> Result<()> {
+        TestClientBuilder::new().run(|_| {
+            let client = Aocd::new(2022, 1, Some("no-such-file.txt"))
+                .with_test_input("1\n2\n")
+                .with_expected_answer(1, 3);
+            assert_eq!(client.get_input(), "1\n2");
+            client.submit(1, 3);
+            assert!(!client.failed.load(Ordering::Relaxed));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_puzzle_input_skipped_after_failed_example() -> Result<()> {
+        TestClientBuilder::new().run(|_| {
+            let example =
+                Aocd::new(2022, 1, Some("tests/inputs/2022-01.txt")).with_expected_answer(1, 24000);
+            let real = Aocd::new(2022, 1, None);
+            assert!(example.begin(&[]));
+            example.submit(1, 24000);
+            assert!(real.begin(std::slice::from_ref(&example)));
+            example.submit(1, 1);
+            assert!(!real.begin(std::slice::from_ref(&example)));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_cached_client_checks_correct_answers() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url("http://localhost:1")
+            .run(|client| {
+                assert!(Aocd::cached(2022, 1).is_none());
+                client.cache.cache_input("1\n2")?;
+                client.cache.cache_answer_response(1, "3", "", true)?;
+
+                let cached = Aocd::cached(2022, 1).unwrap();
+                assert_eq!(cached.get_input(), "1\n2");
+                cached.submit(1, 3);
+                assert!(!cached.failed());
+                cached.submit(2, 3);
+                assert!(cached.failed());
+                assert!(cached.send(cached.puzzle_page_request()).is_err());
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_verify() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_VERIFY", "1")
+            .url("http://localhost:1")
+            .run(|client| {
+                client.cache.cache_answer_response(1, "3", "", true)?;
+                client.submit(1, 3);
+                assert!(!client.failed());
+                client.submit(1, 4);
+                assert!(client.failed());
+
+                let client = Aocd::new(2022, 1, None).with_verify(false);
+                assert!(client.answer_request(2, "4").is_some());
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_dry_run() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_DRY_RUN", "true")
+            .input("1\n2\n")
+            .run(|client| {
+                assert_eq!(client.get_input(), "1\n2");
+                client
+                    .cache
+                    .cache_answer_response(1, "2", "Too low", false)?;
+                assert!(client.answer_request(1, "2").is_none());
+                assert!(client.answer_request(1, "3").is_none());
+                client.cache.cache_answer_response(1, "3", "", true)?;
+                assert!(client.answer_request(1, "4").is_none());
+                assert!(!client.failed());
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_confirm_submission() -> Result<()> {
+        TestClientBuilder::new().run(|client| {
+            assert_eq!(client.previous_guesses(1), "");
+            client.cache.cache_answer_response(1, "10", "That's not the right answer; your answer is too low.", false)?;
+            client.cache.cache_answer_response(1, "50", "That's not the right answer; your answer is too high.", false)?;
+            client.cache.cache_answer_response(1, "90", "That's not the right answer; your answer is too high.", false)?;
+            client.cache.cache_answer_response(1, "x", "That's not the right answer.", false)?;
+            client.cache.cache_answer_response(2, "20", "That's not the right answer.", false)?;
+            assert_eq!(
+                client.previous_guesses(1),
+                "Previous guesses for part 1:\n  10 (too low)\n  50 (too high)\n  90 (too high)\n  x\n\
+                 The answer is above 10 and below 50\n"
+            );
+
+            assert!(client.confirm_submission(1, "20", &mut "y\n".as_bytes()));
+            assert!(client.confirm_submission(1, "20", &mut " Yes\n".as_bytes()));
+            assert!(!client.confirm_submission(1, "20", &mut "\n".as_bytes()));
+            assert!(!client.confirm_submission(1, "20", &mut "".as_bytes()));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_answer_checks() -> Result<()> {
+        TestClientBuilder::new().run(|client| {
+            for answer in [
+                "",
+                " ",
+                "1 2",
+                "12\n",
+                "Some(12)",
+                "None",
+                "Err(\"bad\")",
+                "\"12\"",
+                "0",
+            ] {
+                assert!(client.answer_problem(1, answer).is_some(), "{answer:?}");
+                assert!(client.answer_request(1, answer).is_none(), "{answer:?}");
+            }
+            for answer in ["12", "-3", "abc", "1,2,3", "\""] {
+                assert_eq!(client.answer_problem(1, answer), None, "{answer:?}");
+            }
+
+            client.cache.cache_answer_response(1, "12", "", true)?;
+            assert!(client.answer_problem(2, "12").is_some());
+            assert!(client.answer_request(2, "12").is_none());
+
+            let client = Aocd::new(client.year, client.day, None).with_answer_checks(false);
+            assert!(client.answer_request(2, "12").is_some());
+            Ok(())
+        })
+    }
+
+    #[test]
+    #[should_panic(expected = "Part should be 1 or 2, not 3")]
+    fn test_submit_invalid_part() {
+        let _ = TestClientBuilder::new().run(|client| {
+            client.submit(3, 1);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_no_part_2_on_last_day() -> Result<()> {
+        TestClientBuilder::new().day(25).run(|client| {
+            assert!(client.answer_request(2, "1").is_none());
+            assert!(client.answer_request(1, "1").is_some());
+
+            let client = Aocd::new(2025, 12, None);
+            assert!(client.answer_request(2, "1").is_none());
+            assert_eq!(client.missing_stars().len(), 23);
+            Ok(())
+        })
+    }
+
+    #[test]
+    #[should_panic(expected = "Advent of Code 2025 has no day 13. The days are 1 to 12.")]
+    fn test_day_outside_event() {
+        let _ = TestClientBuilder::new().year(2025).day(13).run(|_| Ok(()));
+    }
+
+    #[test]
+    fn test_claim_final_star() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 25).answer(1, "2=-1=0").solved(1));
+        TestClientBuilder::new()
+            .year(2022)
+            .day(25)
+            .url(&server.url())
+            .run(|client| {
+                assert_eq!(client.missing_stars().len(), 49);
+                // Nothing is sent while stars are missing.
+                client.claim_final_star();
+                for day in 1..=25 {
+                    let cache = cache::Cache::new(2022, day, "test-session")?;
+                    cache.cache_answer_response(1, "1", "", true)?;
+                    if day < 25 {
+                        cache.cache_answer_response(2, "2", "", true)?;
+                    }
+                }
+                assert_eq!(client.missing_stars(), vec![]);
+
+                client.submit(2, 0);
+                client.claim_final_star();
+                assert_eq!(client.cache.get_correct_answer(2)?, "0");
+                assert!(client.final_star_request().is_none());
+                Ok(())
+            })?;
+        assert_eq!(server.submissions().len(), 1);
+        assert_eq!(server.submissions()[0].part, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("45000", "45010"),
+            "  expected: 45000\n  actual:   45010\n               ^"
+        );
+    }
+
+    #[test]
+    fn test_custom_transport() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(3)
+            .var("AOC_CONTACT", "me@example.com")
+            .run(|client| {
+                static REQUESTS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+                let client =
+                    Aocd::new(client.year, client.day, None).with_transport(|request: &Request| {
+                        REQUESTS.lock().unwrap().push(request.url.clone());
+                        assert_eq!(request.headers["Cookie"], "session=test-session");
+                        assert_eq!(
+                            request.headers["User-Agent"],
+                            "aocd/0.4.0 (github.com/bsamseth/aocd by me@example.com)"
+                        );
+                        Ok(Response {
+                            status_code: 200,
+                            headers: Default::default(),
+                            body: "input from transport\n".to_string(),
+                        })
+                    });
+
+                assert_eq!(client.get_input(), "input from transport");
+                assert_eq!(client.get_input(), "input from transport");
+                assert_eq!(
+                    *REQUESTS.lock().unwrap(),
+                    vec![format!("{}/2022/day/3/input", mockito::server_url())]
+                );
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_retry_gets_but_not_posts() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(4)
+            .var("AOC_RETRY_DELAY", "0")
+            .run(|client| {
+                static ATTEMPTS: std::sync::Mutex<Vec<Method>> = std::sync::Mutex::new(Vec::new());
+                let client =
+                    Aocd::new(client.year, client.day, None).with_transport(|request: &Request| {
+                        let mut attempts = ATTEMPTS.lock().unwrap();
+                        attempts.push(request.method);
+                        if attempts.len() < 3 || request.method == Method::Post {
+                            return Err(anyhow!("Connection reset by peer"));
+                        }
+                        Ok(Response {
+                            status_code: 200,
+                            headers: Default::default(),
+                            body: "input".to_string(),
+                        })
+                    });
+
+                assert_eq!(client.get_input(), "input");
+                let submitted =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| client.submit(1, 42)));
+                assert!(submitted.is_err());
+                assert_eq!(
+                    *ATTEMPTS.lock().unwrap(),
+                    vec![Method::Get, Method::Get, Method::Get, Method::Post]
+                );
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_puzzle() -> Result<()> {
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(
+            FakePuzzle::new(2022, 1)
+                .title("Calorie Counting")
+                .description(1, "<p>Count the calories.</p>")
+                .description(2, "<p>Count the top three.</p>")
+                .answer(1, "24000"),
+        );
+
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .run(|client| {
+                let puzzle = client.puzzle();
+                assert_eq!(puzzle.title, "Calorie Counting");
+                assert!(puzzle.part1.contains("Count the calories."));
+                assert_eq!(puzzle.part2, None);
+                assert_eq!(puzzle.stars, 0);
+                assert_eq!(client.puzzle(), puzzle);
+
+                // Solving part 1 unlocks part 2, so the page is fetched again.
+                client.submit(1, 24000);
+                let puzzle = client.puzzle();
+                assert!(puzzle.part2.unwrap().contains("Count the top three."));
+                assert_eq!(puzzle.answers[0].as_deref(), Some("24000"));
+                assert_eq!(puzzle.stars, 1);
+                Ok(())
+            })?;
+
+        let pages = server
+            .requests()
+            .iter()
+            .filter(|r| r.path == "/2022/day/1")
+            .count();
+        assert_eq!(pages, 2);
+        Ok(())
+    }
+
+    fn cassette(name: &str) -> String {
+        format!("{}/tests/cassettes/{name}.json", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_replay_wrong_then_right_answer() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_REPLAY", &cassette("wrong-then-right"))
+            .run(|client| {
+                client.submit(1, 123);
+                assert!(client
+                    .cache
+                    .get_answer_response(1, "123")?
+                    .contains("your answer is too low"));
+                assert!(client.cache.get_correct_answer(1).is_err());
+
+                client.submit(1, 24000);
+                assert_eq!(client.cache.get_correct_answer(1)?, "24000");
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_replay_answered_too_recently() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_REPLAY", &cassette("answered-too-recently"))
+            .run(|client| {
+                client.submit(1, 123);
+                // Being rate limited says nothing about the answer, so nothing should be cached.
+                assert!(client.cache.get_answer_response(1, "123").is_err());
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_replay_already_completed() -> Result<()> {
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_REPLAY", &cassette("already-completed"))
+            .run(|client| {
+                client.submit(2, 45000);
+                assert_eq!(client.cache.get_correct_answer(1)?, "24000");
+                assert_eq!(client.cache.get_correct_answer(2)?, "45000");
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_record_then_replay() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cassette.json");
+        let path = path.to_str().unwrap();
+
+        let server = FakeServer::new("test-session");
+        server.add_puzzle(FakePuzzle::new(2022, 1).input("1\n2").answer(1, "3"));
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .url(&server.url())
+            .var("AOC_RECORD", path)
+            .run(|client| {
+                assert_eq!(client.get_input(), "1\n2");
+                client.submit(1, 3);
+                Ok(())
+            })?;
+        drop(server);
+
+        let recorded = std::fs::read_to_string(path)?;
+        assert!(!recorded.contains("test-session"));
+
+        TestClientBuilder::new()
+            .year(2022)
+            .day(1)
+            .var("AOC_REPLAY", path)
+            .run(|client| {
+                assert_eq!(client.get_input(), "1\n2");
+                client.submit(1, 3);
+                assert_eq!(client.cache.get_correct_answer(1)?, "3");
+                Ok(())
+            })
     }
 
     #[test]
@@ -0,0 +1,116 @@
+//! Settings, read from environment variables or the config file.
+//!
+//! Every setting `name` is read from the environment variable `AOC_NAME` if set, and otherwise
+//! from a line `name = value` in the config file, `~/.config/aocd/config` (or the file given by
+//! `AOC_CONFIG_PATH`). Lines starting with `#` in the config file are ignored.
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub struct Config {
+    file: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let path = std::env::var("AOC_CONFIG_PATH")
+            .unwrap_or_else(|_| shellexpand::tilde("~/.config/aocd/config").to_string());
+        let file = std::fs::read_to_string(path)
+            .map(|contents| parse(&contents))
+            .unwrap_or_default();
+        Self { file }
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        std::env::var(format!("AOC_{}", name.to_uppercase()))
+            .ok()
+            .or_else(|| self.file.get(name).cloned())
+            .map(|value| value.trim().to_string())
+    }
+
+    /// A setting parsed as `T`.
+    ///
+    /// # Panics
+    /// Panics if the setting can't be parsed as `T`.
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("Setting {name} has an invalid value: {value:?}"))
+        })
+    }
+
+    /// A setting that is on or off, given as `1`/`0`, `true`/`false`, `yes`/`no` or `on`/`off`.
+    ///
+    /// # Panics
+    /// Panics if the setting is not one of these.
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        self.get(name)
+            .map(|value| match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" | "" => false,
+                _ => panic!("Setting {name} should be on (1) or off (0), not {value:?}"),
+            })
+    }
+
+    /// A duration given in (possibly fractional) seconds.
+    ///
+    /// # Panics
+    /// Panics if the setting is not a valid number of seconds.
+    pub fn duration(&self, name: &str) -> Option<Duration> {
+        self.get(name).map(|value| {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .unwrap_or_else(|| {
+                    panic!("Setting {name} should be a number of seconds, not {value:?}")
+                })
+        })
+    }
+}
+
+fn parse(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_env_overrides_file() -> anyhow::Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(
+            file,
+            "# A comment\ncontact = me@example.com\nthrottle = 2.5\nverify = yes"
+        )?;
+
+        temp_env::with_vars(
+            [
+                ("AOC_CONFIG_PATH", file.path().to_str()),
+                ("AOC_CONTACT", None),
+                ("AOC_THROTTLE", Some("0.5")),
+                ("AOC_VERIFY", None),
+            ],
+            || {
+                let config = Config::load();
+                assert_eq!(config.get("contact").as_deref(), Some("me@example.com"));
+                assert_eq!(
+                    config.duration("throttle"),
+                    Some(Duration::from_millis(500))
+                );
+                assert_eq!(config.flag("verify"), Some(true));
+                assert_eq!(config.get("missing"), None);
+            },
+        );
+        Ok(())
+    }
+}
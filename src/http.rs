@@ -0,0 +1,400 @@
+//! The HTTP layer used by the [`Aocd`](crate::Aocd) client.
+//!
+//! All requests the client makes go through an [`HttpTransport`]. By default this is
+//! [`MinreqTransport`], but any other implementation can be injected with
+//! [`Aocd::with_transport`](crate::Aocd::with_transport), e.g. an in-process fake for unit tests,
+//! a wrapper adding instrumentation, or a different HTTP client altogether.
+//!
+//! # Example
+//! ```no_run
+//! use aocd::http::{HttpTransport, MinreqTransport, Request, Response};
+//!
+//! struct Logging(MinreqTransport);
+//!
+//! impl HttpTransport for Logging {
+//!     fn send(&self, request: &Request) -> anyhow::Result<Response> {
+//!         let response = self.0.send(request)?;
+//!         eprintln!("{:?} {} -> {}", request.method, request.url, response.status_code);
+//!         Ok(response)
+//!     }
+//! }
+//!
+//! let client = aocd::Aocd::new(2022, 1, None).with_transport(Logging(MinreqTransport::default()));
+//! ```
+//!
+//! With the `async` feature, the `AsyncAocd` client uses an `AsyncHttpTransport` in the same way.
+use std::collections::BTreeMap;
+use std::sync::Once;
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    pub status_code: i32,
+    /// Header names are lowercase.
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl Request {
+    #[must_use]
+    pub fn get(url: String) -> Self {
+        Self {
+            method: Method::Get,
+            url,
+            headers: BTreeMap::new(),
+            body: None,
+        }
+    }
+
+    #[must_use]
+    pub fn post(url: String, body: String) -> Self {
+        Self {
+            method: Method::Post,
+            url,
+            headers: BTreeMap::new(),
+            body: Some(body),
+        }
+    }
+
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+/// Something that can send HTTP requests.
+///
+/// Responses with non-success status codes should be returned as normal responses, and only
+/// failures to get a response at all should be errors.
+///
+/// This is implemented for closures taking a `&Request`, which is handy for fakes in tests.
+pub trait HttpTransport: Send + Sync {
+    /// Send the request and wait for the response.
+    ///
+    /// # Errors
+    /// Returns an error if no response could be received.
+    fn send(&self, request: &Request) -> Result<Response>;
+}
+
+impl<F> HttpTransport for F
+where
+    F: Fn(&Request) -> Result<Response> + Send + Sync,
+{
+    fn send(&self, request: &Request) -> Result<Response> {
+        self(request)
+    }
+}
+
+/// The default transport, sending requests over the network with [`minreq`].
+///
+/// Proxies are taken from the `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY` environment variables
+/// (or their lowercase versions), except for hosts listed in `NO_PROXY`. A proxy in the lowercase
+/// `https_proxy`, `http_proxy` or `all_proxy` is still used for those hosts, with a warning, since
+/// minreq reads these by itself.
+#[derive(Debug, Clone, Default)]
+pub struct MinreqTransport {
+    timeout: Option<Duration>,
+}
+
+impl MinreqTransport {
+    /// Give up if the whole request, from connecting to reading the response, takes longer than
+    /// this. Rounded up to whole seconds.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl HttpTransport for MinreqTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let mut r = match request.method {
+            Method::Get => minreq::get(&request.url),
+            Method::Post => minreq::post(&request.url),
+        };
+        for (name, value) in &request.headers {
+            r = r.with_header(name, value);
+        }
+        if let Some(body) = &request.body {
+            r = r.with_body(body.as_str());
+        }
+        if let Some(timeout) = self.timeout {
+            r = r.with_timeout(timeout.as_secs_f64().ceil() as u64);
+        }
+        if let Some(proxy) = proxy_for(&request.url) {
+            r = r.with_proxy(
+                minreq::Proxy::new(&proxy).with_context(|| format!("Invalid proxy {proxy}"))?,
+            );
+        } else if let Some(name) = unexempted_proxy(&request.url) {
+            static WARNED: Once = Once::new();
+            WARNED.call_once(|| {
+                eprintln!(
+                    "⚠️ The host is listed in NO_PROXY, but the proxy in {name} is still used for it. \
+                     Unset {name} to reach it directly. ⚠️"
+                );
+            });
+        }
+
+        let response = r.send()?;
+        Ok(Response {
+            status_code: response.status_code,
+            headers: response
+                .headers
+                .iter()
+                .map(|(name, value)| (name.to_lowercase(), value.clone()))
+                .collect(),
+            body: response.as_str()?.to_string(),
+        })
+    }
+}
+
+/// The first of the environment variables that is set and not empty.
+fn env_var(names: &[&'static str]) -> Option<(&'static str, String)> {
+    names.iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| (*name, v))
+    })
+}
+
+/// The proxy to use for the URL according to the environment, if any.
+fn proxy_for(url: &str) -> Option<String> {
+    if excluded_from_proxy(url) {
+        return None;
+    }
+    let names: &[&str] = if url.starts_with("https://") {
+        &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+    } else {
+        &["http_proxy", "HTTP_PROXY", "ALL_PROXY", "all_proxy"]
+    };
+    env_var(names).map(|(_, proxy)| proxy)
+}
+
+/// The variable with the proxy that is used for the URL even though `NO_PROXY` excludes its host.
+///
+/// When no proxy is given, minreq uses the one in `https_proxy` (for HTTPS), or else `http_proxy`
+/// or `all_proxy`, by itself, and there is no way to tell it not to.
+fn unexempted_proxy(url: &str) -> Option<&'static str> {
+    if !excluded_from_proxy(url) {
+        return None;
+    }
+    let names: &[&str] = if url.starts_with("https://") {
+        &["https_proxy"]
+    } else {
+        &["http_proxy", "all_proxy"]
+    };
+    env_var(names).map(|(name, _)| name)
+}
+
+/// Whether `NO_PROXY` lists the host of the URL.
+fn excluded_from_proxy(url: &str) -> bool {
+    let Some((_, no_proxy)) = env_var(&["NO_PROXY", "no_proxy"]) else {
+        return false;
+    };
+    let rest = url.split_once("://").map_or(url, |(_scheme, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _port)| host);
+
+    no_proxy.split(',').map(str::trim).any(|pattern| {
+        let pattern = pattern.rsplit_once(':').map_or(pattern, |(p, _port)| p);
+        let domain = pattern.trim_start_matches('.');
+        pattern == "*"
+            || (!domain.is_empty()
+                && (host.eq_ignore_ascii_case(domain)
+                    || host
+                        .to_lowercase()
+                        .ends_with(&format!(".{}", domain.to_lowercase()))))
+    })
+}
+
+/// Parse an HTTP date, like `Sun, 01 Dec 2024 05:00:00 GMT`, as sent in the `Date` header.
+pub(crate) fn parse_http_date(date: &str) -> Option<SystemTime> {
+    let [_weekday, day, month, year, time, "GMT"] = date.split_whitespace().collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| *m == month)?;
+    let mut time = time.split(':').map(str::parse::<u64>);
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (time.next(), time.next(), time.next(), time.next())
+    else {
+        return None;
+    };
+
+    let days =
+        crate::calendar::days_since_epoch(year.parse().ok()?, month as i64 + 1, day.parse().ok()?);
+    let seconds =
+        u64::try_from(days).ok()? * 24 * 60 * 60 + hours * 60 * 60 + minutes * 60 + seconds;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// A boxed future, as returned by [`AsyncHttpTransport::send`].
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Something that can send HTTP requests asynchronously. The async counterpart of [`HttpTransport`].
+#[cfg(feature = "async")]
+pub trait AsyncHttpTransport: Send + Sync {
+    /// Send the request, and resolve to the response.
+    ///
+    /// Responses with non-success status codes should be returned as normal responses, and only
+    /// failures to get a response at all should be errors.
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response>>;
+}
+
+/// Runs a blocking [`HttpTransport`] on tokio's blocking thread pool.
+///
+/// This is the default transport of [`AsyncAocd`](crate::AsyncAocd), wrapping a [`MinreqTransport`].
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub struct SpawnBlocking<T>(Arc<T>);
+
+#[cfg(feature = "async")]
+impl<T> SpawnBlocking<T> {
+    pub fn new(transport: T) -> Self {
+        Self(Arc::new(transport))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: HttpTransport + 'static> AsyncHttpTransport for SpawnBlocking<T> {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response>> {
+        let transport = Arc::clone(&self.0);
+        let request = request.clone();
+        Box::pin(
+            async move { tokio::task::spawn_blocking(move || transport.send(&request)).await? },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 01 Dec 2024 04:59:58 GMT"),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_733_029_198))
+        );
+        assert_eq!(parse_http_date("Sun, 01 Dec 2024 04:59:58"), None);
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_proxy_for() {
+        temp_env::with_vars(
+            [
+                ("HTTPS_PROXY", Some("http://proxy.corp:3128")),
+                ("https_proxy", None),
+                ("http_proxy", None),
+                ("HTTP_PROXY", None),
+                ("ALL_PROXY", None),
+                ("all_proxy", None),
+                ("NO_PROXY", Some("localhost, .internal.corp")),
+                ("no_proxy", None),
+            ],
+            || {
+                assert_eq!(
+                    proxy_for("https://adventofcode.com/2022/day/1/input").as_deref(),
+                    Some("http://proxy.corp:3128")
+                );
+                assert_eq!(proxy_for("http://adventofcode.com/2022/day/1"), None);
+                assert_eq!(proxy_for("https://localhost:8080/2022/day/1"), None);
+                assert_eq!(proxy_for("https://aoc.internal.corp/2022/day/1"), None);
+                assert_eq!(unexempted_proxy("https://localhost:8080/2022/day/1"), None);
+            },
+        );
+        temp_env::with_vars(
+            [
+                ("https_proxy", Some("http://proxy.corp:3128")),
+                ("http_proxy", None),
+                ("all_proxy", Some("http://proxy.corp:3128")),
+                ("NO_PROXY", Some("localhost")),
+            ],
+            || {
+                assert_eq!(proxy_for("https://localhost/2022/day/1"), None);
+                assert_eq!(
+                    unexempted_proxy("https://localhost/2022/day/1"),
+                    Some("https_proxy")
+                );
+                assert_eq!(
+                    unexempted_proxy("http://localhost/2022/day/1"),
+                    Some("all_proxy")
+                );
+                assert_eq!(
+                    unexempted_proxy("https://adventofcode.com/2022/day/1"),
+                    None
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_transport_no_proxy() {
+        let _m = mockito::mock("GET", "/no-proxy")
+            .with_body("direct")
+            .create();
+        let url = format!("{}/no-proxy", mockito::server_url());
+        let dead_proxy = "http://127.0.0.1:1";
+        let transport = MinreqTransport::default();
+
+        // Exempted from a proxy that only aocd reads.
+        temp_env::with_vars(
+            [
+                ("HTTP_PROXY", Some(dead_proxy)),
+                ("http_proxy", None),
+                ("ALL_PROXY", None),
+                ("all_proxy", None),
+                ("NO_PROXY", Some("127.0.0.1")),
+                ("no_proxy", None),
+            ],
+            || {
+                let response = transport.send(&Request::get(url.clone())).unwrap();
+                assert_eq!(response.body, "direct");
+            },
+        );
+
+        // But not from one that minreq reads by itself, which is warned about instead.
+        temp_env::with_vars(
+            [
+                ("HTTP_PROXY", None),
+                ("http_proxy", Some(dead_proxy)),
+                ("NO_PROXY", Some("127.0.0.1")),
+                ("no_proxy", None),
+            ],
+            || {
+                assert_eq!(unexempted_proxy(&url), Some("http_proxy"));
+                assert!(transport.send(&Request::get(url.clone())).is_err());
+            },
+        );
+    }
+}
@@ -1,7 +1,29 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "async")]
+mod async_client;
 mod cache;
+mod cassette;
 mod client;
+mod config;
+mod part;
+mod puzzle;
+mod throttle;
 
+pub mod answer;
+pub mod calendar;
+pub mod http;
+pub mod ocr;
+#[doc(hidden)]
+pub mod outcome;
 pub mod prelude;
+pub mod render;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub use prelude::*;
+
+pub use crate::client::NotYetUnlocked;
+pub use crate::puzzle::{Example, Puzzle};
+
+#[cfg(feature = "async")]
+pub use crate::async_client::AsyncAocd;
@@ -0,0 +1,223 @@
+//! Reading the capital letters some puzzles draw as their answer, like the screen of 2016 day 8 or
+//! the paper of 2021 day 13.
+//!
+//! Both fonts used by Advent of Code are known: the 6 pixel high one used by most puzzles, and the
+//! 10 pixel high one of 2018 day 10. Letters are separated by blank columns, and lit pixels can be
+//! drawn as `#` or `█`, with anything else being unlit.
+//!
+//! Grids of `bool`s implement [`AocAnswer`], so they can be submitted directly.
+//!
+//! # Example
+//! ```
+//! let drawing = "#..#.####\n#..#.#...\n####.###.\n#..#.#...\n#..#.#...\n#..#.####";
+//! assert_eq!(aocd::ocr::read_letters(drawing).unwrap(), "HE");
+//! ```
+use anyhow::{anyhow, bail, Result};
+
+use crate::answer::AocAnswer;
+
+const FONT_6: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const FONT_10: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Read the letters drawn with `#` (or `█`) in the text.
+///
+/// # Errors
+/// Returns an error if the drawing is not 6 or 10 pixels high, or has a letter that isn't known.
+pub fn read_letters(drawing: &str) -> Result<String> {
+    let grid: Vec<Vec<bool>> = drawing
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#' || c == '█').collect())
+        .collect();
+    read_grid(&grid)
+}
+
+/// Read the letters drawn by the `true` pixels in the grid, given as rows.
+///
+/// # Errors
+/// Returns an error if the drawing is not 6 or 10 pixels high, or has a letter that isn't known.
+pub fn read_grid<R: AsRef<[bool]>>(grid: &[R]) -> Result<String> {
+    let rows: Vec<&[bool]> = grid
+        .iter()
+        .map(AsRef::as_ref)
+        .skip_while(|row| !row.contains(&true))
+        .collect();
+    let height = rows
+        .iter()
+        .rposition(|row| row.contains(&true))
+        .map_or(0, |last| last + 1);
+    let rows = &rows[..height];
+    let font = match height {
+        6 => FONT_6,
+        10 => FONT_10,
+        _ => bail!("Letters should be 6 or 10 pixels high, not {height}"),
+    };
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let lit = |x: usize| rows.iter().any(|row| row.get(x) == Some(&true));
+    let mut letters = String::new();
+    let mut x = 0;
+    while x < width {
+        if !lit(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < width && lit(x) {
+            x += 1;
+        }
+
+        let glyph = rows
+            .iter()
+            .map(|row| {
+                (start..x)
+                    .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let letter = font
+            .iter()
+            .find(|(_, known)| *known == glyph)
+            .map(|(letter, _)| *letter)
+            .ok_or_else(|| anyhow!("Unknown letter {}:\n{glyph}", letters.len() + 1))?;
+        letters.push(letter);
+    }
+    Ok(letters)
+}
+
+impl AocAnswer for Vec<Vec<bool>> {
+    fn to_answer(&self) -> Result<String, String> {
+        read_grid(self).map_err(|e| e.to_string())
+    }
+}
+
+impl<const W: usize, const H: usize> AocAnswer for [[bool; W]; H] {
+    fn to_answer(&self) -> Result<String, String> {
+        read_grid(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_letters_6() {
+        // 2022 day 10, with a blank column after each letter.
+        let screen = "\
+###..#....###...##..####.###...##..#....
+#..#.#....#..#.#..#....#.#..#.#..#.#....
+#..#.#....#..#.#..#...#..###..#....#....
+###..#....###..####..#...#..#.#....#....
+#....#....#....#..#.#....#..#.#..#.#....
+#....####.#....#..#.####.###...##..####.";
+        assert_eq!(read_letters(screen).unwrap(), "PLPAZBCL");
+
+        // Pixels as █, with margins and a wider letter.
+        let screen = "\n\n█   █ ███ \n█   █  █  \n █ █   █  \n  █    █  \n  █    █  \n  █   ███ \n";
+        assert_eq!(read_letters(screen).unwrap(), "YI");
+    }
+
+    #[test]
+    fn test_read_grid_10() {
+        let rows: Vec<Vec<bool>> = FONT_10
+            .iter()
+            .find(|(letter, _)| *letter == 'X')
+            .unwrap()
+            .1
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect();
+        assert_eq!(read_grid(&rows).unwrap(), "X");
+        assert_eq!(rows.to_answer(), Ok("X".to_string()));
+    }
+
+    #[test]
+    fn test_unreadable() {
+        assert!(read_letters("#\n#\n#").is_err());
+        assert!(read_letters("##\n##\n##\n##\n##\n##").is_err());
+        let grid = [[true, false, true]; 6];
+        assert!(grid.to_answer().unwrap_err().contains("Unknown letter 1"));
+    }
+}
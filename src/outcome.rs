@@ -0,0 +1,43 @@
+//! How the `#[aocd]` attribute tells whether a run of the annotated function failed, whatever it
+//! returns.
+//!
+//! `(&result).run_error()` picks [`ResultOutcome`] if the function returns a `Result`, by value,
+//! and otherwise [`OtherOutcome`], by reference, which never sees an error.
+use std::fmt::Debug;
+
+/// The error of a `Result` returned by a run, if any.
+pub trait ResultOutcome {
+    fn run_error(self) -> Option<String>;
+}
+
+impl<T, E: Debug> ResultOutcome for &Result<T, E> {
+    fn run_error(self) -> Option<String> {
+        self.as_ref().err().map(|error| format!("{error:?}"))
+    }
+}
+
+/// Anything else returned by a run, which can't be an error.
+pub trait OtherOutcome {
+    fn run_error(self) -> Option<String>;
+}
+
+impl<T> OtherOutcome for &&T {
+    fn run_error(self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_error() {
+        let failed: Result<usize, String> = Err("no elves".to_string());
+        let passed: Result<usize, String> = Ok(3);
+        assert_eq!((&failed).run_error().as_deref(), Some("\"no elves\""));
+        assert_eq!((&passed).run_error(), None);
+        assert_eq!((&3).run_error(), None);
+        assert_eq!((&()).run_error(), None);
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Error};
+
+/// One of the two parts of a puzzle.
+///
+/// Anywhere a part is taken, a plain number works as well, and is checked to be 1 or 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl From<Part> for u8 {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", u8::from(*self))
+    }
+}
+
+macro_rules! part_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<$t> for Part {
+                type Error = Error;
+
+                fn try_from(part: $t) -> Result<Self, Self::Error> {
+                    match part {
+                        1 => Ok(Part::One),
+                        2 => Ok(Part::Two),
+                        _ => Err(anyhow!("Part should be 1 or 2, not {part}")),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+part_from_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The part, panicking with the conversion error if it isn't one.
+pub(crate) fn to_part<P>(part: P) -> Part
+where
+    P: TryInto<Part>,
+    P::Error: Display,
+{
+    part.try_into().unwrap_or_else(|e| panic!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_conversions() {
+        assert_eq!(Part::try_from(1u8).unwrap(), Part::One);
+        assert_eq!(Part::try_from(2i64).unwrap(), Part::Two);
+        assert_eq!(
+            Part::try_from(3usize).unwrap_err().to_string(),
+            "Part should be 1 or 2, not 3"
+        );
+        assert!(Part::try_from(-1).is_err());
+        assert_eq!(to_part(Part::Two), Part::Two);
+        assert_eq!(u8::from(Part::Two), 2);
+        assert_eq!(Part::One.to_string(), "1");
+    }
+}
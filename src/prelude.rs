@@ -1,2 +1,4 @@
+pub use crate::answer::AocAnswer;
 pub use crate::client::Aocd;
+pub use crate::part::Part;
 pub use aocd_proc::{aocd, input, submit};
@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::render;
+
+/// A puzzle, as described on its page on Advent of Code.
+///
+/// The descriptions are the HTML of the puzzle page, one `<article>` per part, including the
+/// `--- Day N: Title ---` and `--- Part Two ---` headers. Part 2 is only known once part 1 is solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub year: u16,
+    pub day: u8,
+    pub title: String,
+    pub part1: String,
+    pub part2: Option<String>,
+    /// The accepted answers, as shown on the puzzle page.
+    pub answers: [Option<String>; 2],
+    /// The number of stars collected for this puzzle (0, 1 or 2).
+    pub stars: u8,
+}
+
+impl Puzzle {
+    /// Parse the HTML of a puzzle page.
+    ///
+    /// # Errors
+    /// Returns an error if the page does not contain a puzzle description.
+    pub fn parse(year: u16, day: u8, html: &str) -> Result<Self> {
+        let article = Regex::new(r#"(?s)<article class="day-desc">(.*?)</article>"#).unwrap();
+        let mut articles = article
+            .captures_iter(html)
+            .map(|capture| capture[1].trim().to_string());
+        let part1 = articles.next().ok_or_else(|| {
+            anyhow!("No puzzle description found on the page for {year} day {day}")
+        })?;
+        let part2 = articles.next();
+
+        let title = Regex::new(r"<h2>--- Day \d+: (.*?) ---</h2>")
+            .unwrap()
+            .captures(&part1)
+            .map(|capture| capture[1].to_string())
+            .unwrap_or_default();
+
+        let mut answers = [None, None];
+        let answer = Regex::new(r"Your puzzle answer was <code>(.*?)</code>").unwrap();
+        for (slot, capture) in answers.iter_mut().zip(answer.captures_iter(html)) {
+            *slot = Some(capture[1].to_string());
+        }
+
+        // The last day has no answer for part 2, so the stars are counted from the success message if possible.
+        let stars = if html.contains("Both parts of this puzzle are complete") {
+            2
+        } else if html.contains("The first half of this puzzle is complete") {
+            1
+        } else {
+            u8::try_from(answers.iter().flatten().count()).unwrap_or(2)
+        };
+
+        Ok(Self {
+            year,
+            day,
+            title: html_unescape(&title),
+            part1,
+            part2,
+            answers,
+            stars,
+        })
+    }
+}
+
+/// An example from a puzzle description: an input, and the answers it should give.
+///
+/// These are found with heuristics, so they are only candidates: the examples are the code blocks
+/// in the description, and the answer of each part is its last emphasized code (which is where the
+/// expected result is stated for most puzzles). The answers are attached to the first example of
+/// the part they are found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    pub answer_a: Option<String>,
+    pub answer_b: Option<String>,
+}
+
+impl Puzzle {
+    /// Candidate examples from the puzzle description. See [`Example`].
+    #[must_use]
+    pub fn examples(&self) -> Vec<Example> {
+        let block = Regex::new(r"(?s)<pre><code>(.*?)</code></pre>").unwrap();
+        let answer = Regex::new(r"(?s)<code><em>(.*?)</em></code>").unwrap();
+        let tag = Regex::new(r"<[^>]*>").unwrap();
+        let text = |html: &str| html_unescape(&tag.replace_all(html, ""));
+
+        let mut examples: Vec<Example> = Vec::new();
+        for (part, description) in [Some(&self.part1), self.part2.as_ref()]
+            .into_iter()
+            .enumerate()
+        {
+            let Some(description) = description else {
+                break;
+            };
+            let first_new = examples.len();
+            for capture in block.captures_iter(description) {
+                let input = text(&capture[1]).trim_end_matches('\n').to_string();
+                if !examples.iter().any(|e| e.input == input) {
+                    examples.push(Example {
+                        input,
+                        answer_a: None,
+                        answer_b: None,
+                    });
+                }
+            }
+
+            // Part 2 usually reuses the example of part 1.
+            let index = if first_new < examples.len() {
+                first_new
+            } else {
+                0
+            };
+            let Some(example) = examples.get_mut(index) else {
+                continue;
+            };
+            let answer = answer
+                .captures_iter(description)
+                .last()
+                .map(|capture| text(&capture[1]));
+            if part == 0 {
+                example.answer_a = answer;
+            } else {
+                example.answer_b = answer;
+            }
+        }
+        examples
+    }
+
+    /// Write the candidate examples to `example1.txt`, `example2.txt`, etc. in the given directory,
+    /// for use as test files, and return them.
+    ///
+    /// # Errors
+    /// Returns an error if the files could not be written.
+    pub fn write_examples(&self, directory: impl AsRef<Path>) -> std::io::Result<Vec<Example>> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+        let examples = self.examples();
+        for (i, example) in examples.iter().enumerate() {
+            std::fs::write(
+                directory.join(format!("example{}.txt", i + 1)),
+                format!("{}\n", example.input),
+            )?;
+        }
+        Ok(examples)
+    }
+
+    /// The puzzle description as ANSI-styled text for a terminal. See [`render::to_terminal`].
+    #[must_use]
+    pub fn terminal_text(&self) -> String {
+        render::to_terminal(&self.html())
+    }
+
+    /// The puzzle description as Markdown. See [`render::to_markdown`].
+    #[must_use]
+    pub fn markdown(&self) -> String {
+        render::to_markdown(&self.html())
+    }
+
+    /// The descriptions of the unlocked parts, each followed by its answer if known, like on the
+    /// puzzle page.
+    fn html(&self) -> String {
+        let mut html = String::new();
+        for (description, answer) in [Some(&self.part1), self.part2.as_ref()]
+            .into_iter()
+            .zip(&self.answers)
+        {
+            let Some(description) = description else {
+                break;
+            };
+            html += description;
+            if let Some(answer) = answer {
+                html += &format!("<p>Your puzzle answer was <code>{answer}</code>.</p>");
+            }
+        }
+        html
+    }
+}
+
+/// Replace the HTML entities used on Advent of Code with the characters they represent.
+pub(crate) fn html_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unsolved() -> Result<()> {
+        let html = r#"<main>
+<article class="day-desc"><h2>--- Day 1: Calorie Counting ---</h2><p>Find the Elf carrying the most Calories.</p>
+</article>
+<form method="post" action="1/answer"><input type="hidden" name="level" value="1"/></form>
+</main>"#;
+        let puzzle = Puzzle::parse(2022, 1, html)?;
+        assert_eq!(puzzle.title, "Calorie Counting");
+        assert!(puzzle.part1.contains("Find the Elf"));
+        assert_eq!(puzzle.part2, None);
+        assert_eq!(puzzle.answers, [None, None]);
+        assert_eq!(puzzle.stars, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_solved() -> Result<()> {
+        let html = r#"<main>
+<article class="day-desc"><h2>--- Day 2: Rock Paper Scissors ---</h2><p>Part one.</p></article>
+<p>Your puzzle answer was <code>15</code>.</p><article class="day-desc"><h2 id="part2">--- Part Two ---</h2><p>Part two.</p></article>
+<p>Your puzzle answer was <code>12</code>.</p><p class="day-success">Both parts of this puzzle are complete! They provide two gold stars: **</p>
+</main>"#;
+        let puzzle = Puzzle::parse(2022, 2, html)?;
+        assert_eq!(puzzle.title, "Rock Paper Scissors");
+        assert!(puzzle.part2.as_ref().unwrap().contains("Part two."));
+        assert_eq!(
+            puzzle.answers,
+            [Some("15".to_string()), Some("12".to_string())]
+        );
+        assert_eq!(puzzle.stars, 2);
+        assert_eq!(
+            puzzle.markdown(),
+            "## --- Day 2: Rock Paper Scissors ---\n\nPart one.\n\nYour puzzle answer was `15`.\n\n\
+             ## --- Part Two ---\n\nPart two.\n\nYour puzzle answer was `12`.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_examples() -> Result<()> {
+        let html = r#"<main>
+<article class="day-desc"><h2>--- Day 1: Calorie Counting ---</h2><p>For example:</p>
+<pre><code>1000
+2000
+
+<em>3000</em>
+</code></pre>
+<p>The answer is <code>1000</code>, er, <code><em>5000</em></code>.</p></article>
+<p>Your puzzle answer was <code>70000</code>.</p><article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>In the example above, that's <code><em>3000</em></code> &amp; <code><em>3&lt;000</em></code>.</p>
+<pre><code>x &lt; y</code></pre>
+<p>Also, <code><em>4</em></code>.</p></article>
+</main>"#;
+        let puzzle = Puzzle::parse(2022, 1, html)?;
+        assert_eq!(
+            puzzle.examples(),
+            vec![
+                Example {
+                    input: "1000\n2000\n\n3000".to_string(),
+                    answer_a: Some("5000".to_string()),
+                    answer_b: None,
+                },
+                Example {
+                    input: "x < y".to_string(),
+                    answer_a: None,
+                    answer_b: Some("4".to_string()),
+                }
+            ]
+        );
+
+        let dir = tempfile::tempdir()?;
+        puzzle.write_examples(dir.path())?;
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("example1.txt"))?,
+            "1000\n2000\n\n3000\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("example2.txt"))?,
+            "x < y\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_not_a_puzzle() {
+        assert!(Puzzle::parse(2022, 1, "<main>404 Not Found</main>").is_err());
+    }
+}
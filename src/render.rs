@@ -0,0 +1,363 @@
+//! Conversion of puzzle descriptions from HTML to text, for reading them without a browser.
+//!
+//! These handle the small subset of HTML used in the `<article>`s on Advent of Code: headers,
+//! paragraphs, lists, emphasis, inline code, code blocks and links.
+//!
+//! # Example
+//! ```no_run
+//! let puzzle = aocd::Aocd::new(2022, 1, None).puzzle();
+//! println!("{}", aocd::render::to_terminal(&puzzle.part1));
+//! std::fs::write("README.md", puzzle.markdown()).unwrap();
+//! ```
+use regex::Regex;
+
+use crate::puzzle::html_unescape;
+
+const BOLD: &str = "\x1b[1m";
+const NOT_BOLD: &str = "\x1b[22m";
+const DIM: &str = "\x1b[2m";
+const HEADER: &str = "\x1b[1;33m";
+const CODE: &str = "\x1b[36m";
+const DEFAULT_STYLE: &str = "\x1b[0m";
+const DEFAULT_COLOR: &str = "\x1b[39m";
+
+/// Render puzzle HTML as ANSI-styled text for a terminal.
+///
+/// Emphasis is bold, code is colored, code blocks are indented, and links are numbered footnotes
+/// listed at the end.
+#[must_use]
+pub fn to_terminal(html: &str) -> String {
+    Renderer::new(Format::Terminal).render(html)
+}
+
+/// Render puzzle HTML as Markdown.
+#[must_use]
+pub fn to_markdown(html: &str) -> String {
+    Renderer::new(Format::Markdown).render(html)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Terminal,
+    Markdown,
+}
+
+struct Renderer {
+    format: Format,
+    out: String,
+    /// Contents of the `<pre>` block being rendered, if any.
+    pre: Option<String>,
+    /// Contents of the inline `<code>` being rendered, if any, and whether it is emphasized.
+    code: Option<(String, bool)>,
+    /// The targets of the links being rendered, innermost last.
+    open_links: Vec<String>,
+    footnotes: Vec<String>,
+}
+
+impl Renderer {
+    fn new(format: Format) -> Self {
+        Self {
+            format,
+            out: String::new(),
+            pre: None,
+            code: None,
+            open_links: Vec::new(),
+            footnotes: Vec::new(),
+        }
+    }
+
+    fn render(mut self, html: &str) -> String {
+        let tag = Regex::new(r"<(/?)([a-zA-Z0-9]+)([^>]*)>").unwrap();
+        let href = Regex::new(r#"href="([^"]*)""#).unwrap();
+
+        let mut position = 0;
+        for capture in tag.captures_iter(html) {
+            let whole = capture.get(0).unwrap();
+            self.text(&html[position..whole.start()]);
+            position = whole.end();
+
+            let closing = !capture[1].is_empty();
+            let name = capture[2].to_lowercase();
+            let href = href
+                .captures(&capture[3])
+                .map(|h| html_unescape(&h[1]))
+                .unwrap_or_default();
+            if closing {
+                self.close(&name);
+            } else {
+                self.open(&name, &href);
+            }
+        }
+        self.text(&html[position..]);
+
+        let mut out = self.out.trim_end().to_string();
+        if !self.footnotes.is_empty() {
+            out += "\n";
+            for (i, url) in self.footnotes.iter().enumerate() {
+                out += &format!("\n{DIM}[{}]: {url}{DEFAULT_STYLE}", i + 1);
+            }
+        }
+        out + "\n"
+    }
+
+    fn text(&mut self, raw: &str) {
+        let text = html_unescape(raw);
+        if let Some(pre) = &mut self.pre {
+            *pre += &text;
+            return;
+        }
+
+        // Outside of code blocks, whitespace (including newlines in the HTML) is collapsed.
+        let mut collapsed = String::new();
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed += word;
+        }
+        if text.starts_with(char::is_whitespace) && !self.at_line_start() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.trim().is_empty() {
+            collapsed.push(' ');
+        }
+
+        if let Some((code, _)) = &mut self.code {
+            *code += &collapsed;
+        } else if self.format == Format::Markdown {
+            self.out += &escape_markdown(&collapsed);
+        } else {
+            self.out += &collapsed;
+        }
+    }
+
+    fn open(&mut self, name: &str, href: &str) {
+        match (name, self.format) {
+            ("h2", Format::Terminal) => {
+                self.block_break();
+                self.out += HEADER;
+            }
+            ("h2", Format::Markdown) => {
+                self.block_break();
+                self.out += "## ";
+            }
+            ("p", _) => self.block_break(),
+            ("ul", _) => self.block_break(),
+            ("li", Format::Terminal) => {
+                self.line_break();
+                self.out += "  • ";
+            }
+            ("li", Format::Markdown) => {
+                self.line_break();
+                self.out += "- ";
+            }
+            ("br", _) => self.push("\n"),
+            ("pre", _) => {
+                self.block_break();
+                self.pre = Some(String::new());
+            }
+            ("code", _) if self.pre.is_none() && self.code.is_none() => {
+                self.code = Some((String::new(), false));
+            }
+            ("em", _) => {
+                if let Some((_, emphasized)) = &mut self.code {
+                    *emphasized = true;
+                } else if self.format == Format::Terminal {
+                    self.push(BOLD);
+                } else if self.pre.is_none() {
+                    self.out += "*";
+                }
+            }
+            ("a", _) if self.code.is_none() && self.pre.is_none() => {
+                self.open_links.push(absolute_url(href));
+                if self.format == Format::Markdown {
+                    self.out += "[";
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match (name, self.format) {
+            ("h2", Format::Terminal) => {
+                self.out += DEFAULT_STYLE;
+                self.block_break();
+            }
+            ("h2" | "p" | "ul", _) => self.block_break(),
+            ("pre", _) => {
+                if let Some(pre) = self.pre.take() {
+                    self.code_block(&pre);
+                }
+            }
+            ("code", _) if self.pre.is_none() => {
+                if let Some((code, emphasized)) = self.code.take() {
+                    self.inline_code(&code, emphasized);
+                }
+            }
+            ("em", _) => {
+                if self.code.is_some() {
+                    // Emphasis inside inline code is applied to the whole code span.
+                } else if self.format == Format::Terminal {
+                    self.push(NOT_BOLD);
+                } else if self.pre.is_none() {
+                    self.out += "*";
+                }
+            }
+            ("a", _) if self.code.is_none() && self.pre.is_none() => {
+                if let Some(url) = self.open_links.pop() {
+                    match self.format {
+                        Format::Markdown => self.out += &format!("]({url})"),
+                        Format::Terminal => {
+                            self.footnotes.push(url);
+                            self.out += &format!("{DIM}[{}]{DEFAULT_STYLE}", self.footnotes.len());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn inline_code(&mut self, code: &str, emphasized: bool) {
+        match self.format {
+            Format::Terminal => {
+                let bold = if emphasized { BOLD } else { "" };
+                self.out += &format!("{CODE}{bold}{code}{DEFAULT_STYLE}");
+            }
+            Format::Markdown => {
+                let fence = if code.contains('`') { "``" } else { "`" };
+                let padding = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                let code = format!("{fence}{padding}{code}{padding}{fence}");
+                if emphasized {
+                    self.out += &format!("**{code}**");
+                } else {
+                    self.out += &code;
+                }
+            }
+        }
+    }
+
+    fn code_block(&mut self, code: &str) {
+        let code = code.trim_end_matches('\n');
+        match self.format {
+            Format::Terminal => {
+                for line in code.lines() {
+                    self.out += &format!("    {CODE}{line}{DEFAULT_COLOR}\n");
+                }
+            }
+            Format::Markdown => {
+                self.out += &format!("```\n{code}\n```\n");
+            }
+        }
+        self.block_break();
+    }
+
+    /// Add to the output, or to the code block being rendered.
+    fn push(&mut self, s: &str) {
+        match &mut self.pre {
+            Some(pre) => *pre += s,
+            None => self.out += s,
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n') || self.out.ends_with("- ")
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn block_break(&mut self) {
+        self.line_break();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with('/') {
+        format!("https://adventofcode.com{href}")
+    } else {
+        href.to_string()
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<h2>--- Day 1: Calorie Counting ---</h2><p>The Elves take turns writing down the number of <em>Calories</em> contained by the various meals, snacks, rations, <a href="/2022/about">etc.</a> that they've brought with them, one item per line.</p>
+<p>For example:</p>
+<pre><code>1000
+2000
+
+<em>3000</em>
+</code></pre>
+<ul>
+<li>The first Elf is carrying food with <code>1000</code> Calories.</li>
+<li>The fourth Elf is carrying <code><em>24000</em></code> Calories &amp; a *star*.</li>
+</ul>
+"#;
+
+    #[test]
+    fn test_to_markdown() {
+        assert_eq!(
+            to_markdown(ARTICLE),
+            r"## --- Day 1: Calorie Counting ---
+
+The Elves take turns writing down the number of *Calories* contained by the various meals, snacks, rations, [etc.](https://adventofcode.com/2022/about) that they've brought with them, one item per line.
+
+For example:
+
+```
+1000
+2000
+
+3000
+```
+
+- The first Elf is carrying food with `1000` Calories.
+- The fourth Elf is carrying **`24000`** Calories & a \*star\*.
+"
+        );
+    }
+
+    #[test]
+    fn test_to_terminal() {
+        let text = to_terminal(ARTICLE);
+        assert!(text.starts_with(&format!(
+            "{HEADER}--- Day 1: Calorie Counting ---{DEFAULT_STYLE}\n\n"
+        )));
+        assert!(text.contains(&format!("number of {BOLD}Calories{NOT_BOLD} contained")));
+        assert!(text.contains(&format!("etc.{DIM}[1]{DEFAULT_STYLE} that")));
+        assert!(text.contains(&format!("    {CODE}{BOLD}3000{NOT_BOLD}{DEFAULT_COLOR}\n")));
+        assert!(text.contains(&format!(
+            "  • The fourth Elf is carrying {CODE}{BOLD}24000{DEFAULT_STYLE} Calories"
+        )));
+        assert!(text.ends_with(&format!(
+            "\n\n{DIM}[1]: https://adventofcode.com/2022/about{DEFAULT_STYLE}\n"
+        )));
+    }
+}
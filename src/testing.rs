@@ -0,0 +1,511 @@
+//! A local fake Advent of Code server, for testing full input/submit flows without the network.
+//!
+//! Enable the `testing` feature to use this from your own tests. The server listens on a random
+//! local port, and the [`Aocd`](crate::Aocd) client can be pointed at it through the `AOC_URL`
+//! environment variable.
+//!
+//! # Example
+//! ```no_run
+//! use aocd::testing::{FakePuzzle, FakeServer};
+//!
+//! let server = FakeServer::new("test-session");
+//! server.add_puzzle(
+//!     FakePuzzle::new(2022, 1)
+//!         .title("Calorie Counting")
+//!         .input("1000\n2000\n\n3000")
+//!         .answer(1, "3000")
+//!         .answer(2, "6000"),
+//! );
+//!
+//! std::env::set_var("AOC_URL", server.url());
+//! std::env::set_var("AOC_SESSION", "test-session");
+//! std::env::set_var("AOC_CONFIRM", "0");
+//! let client = aocd::Aocd::new(2022, 1, None);
+//! client.submit(1, 3000);
+//! assert_eq!(server.submissions().len(), 1);
+//! ```
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A puzzle known to the [`FakeServer`].
+#[derive(Debug, Clone)]
+pub struct FakePuzzle {
+    year: u16,
+    day: u8,
+    title: String,
+    input: String,
+    descriptions: [String; 2],
+    answers: [Option<String>; 2],
+    solved: [bool; 2],
+}
+
+impl FakePuzzle {
+    /// A puzzle with an empty input, generic descriptions and no known answers.
+    #[must_use]
+    pub fn new(year: u16, day: u8) -> Self {
+        Self {
+            year,
+            day,
+            title: format!("Puzzle {day}"),
+            input: String::new(),
+            descriptions: [
+                "<p>Solve part one.</p>".to_string(),
+                "<p>Solve part two.</p>".to_string(),
+            ],
+            answers: [None, None],
+            solved: [false, false],
+        }
+    }
+
+    /// Set the puzzle title, as shown in the `--- Day N: Title ---` header.
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Set the puzzle input served from `/{year}/day/{day}/input`.
+    #[must_use]
+    pub fn input(mut self, input: &str) -> Self {
+        self.input = input.to_string();
+        self
+    }
+
+    /// Set the HTML description of the given part (the contents of its `<article>`, minus the header).
+    #[must_use]
+    pub fn description(mut self, part: u8, html: &str) -> Self {
+        self.descriptions[part_index(part)] = html.to_string();
+        self
+    }
+
+    /// Set the correct answer for the given part.
+    #[must_use]
+    pub fn answer(mut self, part: u8, answer: &str) -> Self {
+        self.answers[part_index(part)] = Some(answer.to_string());
+        self
+    }
+
+    /// Mark the given part as already solved, as if it was solved before the test started.
+    #[must_use]
+    pub fn solved(mut self, part: u8) -> Self {
+        self.solved[part_index(part)] = true;
+        self
+    }
+
+    fn page(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en-us\">\n<body>\n<main>\n<article class=\"day-desc\"><h2>--- Day {}: {} ---</h2>{}</article>\n",
+            self.day, self.title, self.descriptions[0]
+        );
+        if self.solved[0] {
+            html += &self.answer_paragraph(0);
+            html += &format!(
+                "<article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>{}</article>\n",
+                self.descriptions[1]
+            );
+        }
+        if self.solved[1] {
+            html += &self.answer_paragraph(1);
+            html += "<p class=\"day-success\">Both parts of this puzzle are complete! They provide two gold stars: **</p>\n";
+        } else if self.solved[0] {
+            html += "<p class=\"day-success\">The first half of this puzzle is complete! It provides one gold star: *</p>\n";
+        }
+        html += "</main>\n</body>\n</html>\n";
+        html
+    }
+
+    fn answer_paragraph(&self, index: usize) -> String {
+        format!(
+            "<p>Your puzzle answer was <code>{}</code>.</p>\n",
+            self.answers[index].as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// An answer submission received by the [`FakeServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+}
+
+/// A request received by the [`FakeServer`], e.g. `("GET", "/2022/day/1/input")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+}
+
+struct State {
+    session: String,
+    logged_in: bool,
+    puzzles: HashMap<(u16, u8), FakePuzzle>,
+    rate_limit: Duration,
+    last_wrong_answer: Option<Instant>,
+    requests: Vec<Request>,
+    submissions: Vec<Submission>,
+}
+
+/// A fake Advent of Code server running on a background thread.
+///
+/// It serves the puzzle page (`/{year}/day/{day}`), the puzzle input (`/{year}/day/{day}/input`)
+/// and accepts answers (`/{year}/day/{day}/answer`), mimicking the responses of the real site:
+///
+/// - Requests without the configured session cookie are treated as logged out.
+/// - Wrong answers are rate limited for a configurable duration (no limit by default).
+/// - Submitting a part that is already solved, or part 2 before part 1, gives the wrong-level response.
+///
+/// The server is shut down when dropped.
+pub struct FakeServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FakeServer {
+    /// Start a server accepting the given session token.
+    ///
+    /// # Panics
+    /// Panics if no local port could be bound.
+    #[must_use]
+    pub fn new(session: &str) -> Self {
+        let listener =
+            TcpListener::bind("127.0.0.1:0").expect("Should be able to bind a local port");
+        let address = listener
+            .local_addr()
+            .expect("Listener should have an address");
+        let state = Arc::new(Mutex::new(State {
+            session: session.to_string(),
+            logged_in: true,
+            puzzles: HashMap::new(),
+            rate_limit: Duration::ZERO,
+            last_wrong_answer: None,
+            requests: Vec::new(),
+            submissions: Vec::new(),
+        }));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = Arc::clone(&state);
+            let shutdown = Arc::clone(&shutdown);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // A misbehaving client should not take the server down.
+                        let _ignore = handle_connection(stream, &state, address);
+                    }
+                }
+            })
+        };
+
+        Self {
+            address,
+            state,
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// The base URL of the server, e.g. `http://127.0.0.1:12345`.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Add (or replace) a puzzle.
+    pub fn add_puzzle(&self, puzzle: FakePuzzle) {
+        self.state()
+            .puzzles
+            .insert((puzzle.year, puzzle.day), puzzle);
+    }
+
+    /// Require this long between a wrong answer and the next submission.
+    pub fn set_rate_limit(&self, rate_limit: Duration) {
+        self.state().rate_limit = rate_limit;
+    }
+
+    /// Simulate the session expiring (or being restored).
+    pub fn set_logged_in(&self, logged_in: bool) {
+        self.state().logged_in = logged_in;
+    }
+
+    /// All requests received so far, in order.
+    #[must_use]
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+
+    /// All answer submissions received so far, in order.
+    #[must_use]
+    pub fn submissions(&self) -> Vec<Submission> {
+        self.state().submissions.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .expect("Fake server state should not be poisoned")
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the blocking accept so the thread can see the shutdown flag.
+        let _ignore = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ignore = handle.join();
+        }
+    }
+}
+
+fn part_index(part: u8) -> usize {
+    assert!(part == 1 || part == 2, "Part should be 1 or 2, not {part}");
+    usize::from(part - 1)
+}
+
+struct Response {
+    status: u16,
+    location: Option<String>,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self {
+            status: 200,
+            location: None,
+            body,
+        }
+    }
+
+    fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            location: None,
+            body: body.to_string(),
+        }
+    }
+
+    fn redirect(location: String) -> Self {
+        Self {
+            status: 302,
+            location: Some(location),
+            body: String::new(),
+        }
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    state: &Mutex<State>,
+    address: SocketAddr,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let response = {
+        let mut state = state
+            .lock()
+            .expect("Fake server state should not be poisoned");
+        state.requests.push(Request {
+            method: method.clone(),
+            path: path.clone(),
+        });
+        let session = headers
+            .get("cookie")
+            .and_then(|c| c.split(';').find_map(|c| c.trim().strip_prefix("session=")));
+        let logged_in = state.logged_in && session == Some(state.session.as_str());
+        route(&mut state, &method, &path, &body, logged_in, address)
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len()
+    )?;
+    if let Some(location) = response.location {
+        write!(stream, "Location: {location}\r\n")?;
+    }
+    write!(stream, "\r\n{}", response.body)?;
+    stream.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        302 => "Found",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Unknown",
+    }
+}
+
+fn route(
+    state: &mut State,
+    method: &str,
+    path: &str,
+    body: &str,
+    logged_in: bool,
+    address: SocketAddr,
+) -> Response {
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    let (year, day, endpoint) = match segments.as_slice() {
+        [year, "day", day] => (year, day, None),
+        [year, "day", day, endpoint] => (year, day, Some(*endpoint)),
+        _ => return Response::status(404, "404 Not Found"),
+    };
+    let (Ok(year), Ok(day)) = (year.parse::<u16>(), day.parse::<u8>()) else {
+        return Response::status(404, "404 Not Found");
+    };
+
+    match (method, endpoint) {
+        ("GET", None) => match state.puzzles.get(&(year, day)) {
+            Some(puzzle) => Response::ok(puzzle.page()),
+            None => Response::status(404, "404 Not Found"),
+        },
+        ("GET", Some("input")) => {
+            if !logged_in {
+                return Response::status(
+                    400,
+                    "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+                );
+            }
+            match state.puzzles.get(&(year, day)) {
+                Some(puzzle) => Response::ok(format!("{}\n", puzzle.input)),
+                None => Response::status(404, "404 Not Found"),
+            }
+        }
+        // The real site redirects a plain GET of the answer endpoint to the puzzle page.
+        ("GET", Some("answer")) => Response::redirect(format!("http://{address}/{year}/day/{day}")),
+        ("POST", Some("answer")) => {
+            if !logged_in {
+                return Response::status(400, "Please log in to submit answers.\n");
+            }
+            submit(state, year, day, body)
+        }
+        _ => Response::status(404, "404 Not Found"),
+    }
+}
+
+fn submit(state: &mut State, year: u16, day: u8, body: &str) -> Response {
+    let form: HashMap<_, _> = body
+        .split('&')
+        .filter_map(|field| field.split_once('='))
+        .map(|(key, value)| {
+            let value = value.replace('+', " ");
+            let value = urlencoding::decode(&value).map_or(value.clone(), |v| v.into_owned());
+            (key, value)
+        })
+        .collect();
+    let (Some(part), Some(answer)) = (
+        form.get("level").and_then(|l| l.parse::<u8>().ok()),
+        form.get("answer"),
+    ) else {
+        return Response::status(400, "Bad Request");
+    };
+
+    state.submissions.push(Submission {
+        year,
+        day,
+        part,
+        answer: answer.clone(),
+    });
+
+    if let Some(last) = state.last_wrong_answer {
+        let elapsed = last.elapsed();
+        if elapsed < state.rate_limit {
+            let left = (state.rate_limit - elapsed).as_secs() + 1;
+            return article(&format!(
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have {left}s left to wait. [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+            ));
+        }
+    }
+
+    let Some(puzzle) = state.puzzles.get_mut(&(year, day)) else {
+        return Response::status(404, "404 Not Found");
+    };
+    let wrong_level = match part {
+        1 => puzzle.solved[0],
+        2 => !puzzle.solved[0] || puzzle.solved[1],
+        _ => true,
+    };
+    if wrong_level {
+        return article(&format!(
+            "You don't seem to be solving the right level.  Did you already complete it? [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+        ));
+    }
+
+    if part == 2 && !crate::calendar::has_part_two(year, day) {
+        puzzle.solved[1] = true;
+        return article(&format!(
+            "Congratulations! You've finished every puzzle in Advent of Code {year}! I hope you had as much fun solving them as I had making them for you. [<a href=\"/{year}\">Return to Advent Calendar</a>]"
+        ));
+    }
+
+    let index = usize::from(part - 1);
+    let correct = puzzle.answers[index].as_deref();
+    if correct == Some(answer.as_str()) {
+        puzzle.solved[index] = true;
+        return article(&format!(
+            "That's the right answer!  You are one gold star closer to saving your vacation. [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+        ));
+    }
+
+    let hint = match (
+        answer.parse::<i64>(),
+        correct.and_then(|c| c.parse::<i64>().ok()),
+    ) {
+        (Ok(given), Some(correct)) if given > correct => "; your answer is too high",
+        (Ok(given), Some(correct)) if given < correct => "; your answer is too low",
+        _ => "",
+    };
+    state.last_wrong_answer = Some(Instant::now());
+    article(&format!(
+        "That's not the right answer{hint}.  If you're stuck, make sure you're using the full input data. Please wait one minute before trying again. [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+    ))
+}
+
+fn article(message: &str) -> Response {
+    Response::ok(format!(
+        "<!DOCTYPE html>\n<html lang=\"en-us\">\n<body>\n<main>\n<article><p>{message}</p></article>\n</main>\n</body>\n</html>\n"
+    ))
+}
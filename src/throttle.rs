@@ -0,0 +1,150 @@
+//! Spacing out of requests to Advent of Code, both within and across processes.
+//!
+//! The time of the latest request is kept in memory and in a file in the cache directory, so that
+//! e.g. several solutions started at once by a script still take turns.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long to try getting the lock on the shared file before throttling within the process only.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How old a lock file must be to be taken as left behind by a process that died holding it.
+const STALE_LOCK: Duration = Duration::from_secs(10);
+
+/// The latest reserved slot, per shared file.
+static LAST_REQUEST: Mutex<BTreeMap<PathBuf, SystemTime>> = Mutex::new(BTreeMap::new());
+
+pub struct Throttle {
+    interval: Duration,
+    file: PathBuf,
+}
+
+impl Throttle {
+    pub fn new(interval: Duration, file: PathBuf) -> Self {
+        Self { interval, file }
+    }
+
+    /// Claim the next free slot for a request, and return how long to wait before sending it.
+    pub fn reserve(&self) -> Duration {
+        if self.interval.is_zero() {
+            return Duration::ZERO;
+        }
+
+        let mut last_requests = LAST_REQUEST
+            .lock()
+            .expect("Throttle should not be poisoned");
+        let last_request = last_requests.entry(self.file.clone()).or_insert(UNIX_EPOCH);
+        let now = SystemTime::now();
+        // Failing to use the shared file (e.g. in a read-only cache) only makes us fall back to
+        // throttling within this process.
+        let shared = self.reserve_shared(now).ok();
+        let in_process = (*last_request + self.interval).max(now);
+        let slot = shared.map_or(in_process, |shared| shared.max(in_process));
+        *last_request = slot;
+
+        slot.duration_since(now).unwrap_or_default()
+    }
+
+    fn reserve_shared(&self, now: SystemTime) -> std::io::Result<SystemTime> {
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.file)?;
+        let _lock = LockFile::acquire(&self.file.with_extension("lock"))?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let slot = contents
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+            .map_or(now, |last| (last + self.interval).max(now));
+
+        let millis = slot
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{millis}")?;
+        Ok(slot)
+    }
+}
+
+/// A file that only one process can create at a time, serving as a lock until dropped.
+struct LockFile(PathBuf);
+
+impl LockFile {
+    fn acquire(path: &Path) -> std::io::Result<Self> {
+        let start = Instant::now();
+        loop {
+            match File::options().write(true).create_new(true).open(path) {
+                Ok(_) => return Ok(Self(path.to_path_buf())),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(path)
+                        .and_then(|m| m.modified())
+                        .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK);
+                    if stale {
+                        let _ = std::fs::remove_file(path);
+                    } else if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(e);
+                    } else {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_spaces_out_requests() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let interval = Duration::from_secs(60);
+        let first = Throttle::new(interval, dir.path().join("last-request"));
+        // Same file, as if in another process.
+        let second = Throttle::new(interval, dir.path().join("last-request"));
+
+        let first_wait = first.reserve();
+        let second_wait = second.reserve();
+        let third_wait = first.reserve();
+
+        assert!(first_wait < Duration::from_secs(1));
+        assert!(second_wait > Duration::from_secs(59));
+        assert!(third_wait > Duration::from_secs(119));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("last-request.lock");
+        let lock = LockFile::acquire(&path)?;
+        assert!(path.exists());
+        drop(lock);
+        assert!(!path.exists());
+
+        // A lock left behind by a process that died is taken over.
+        File::create(&path)?.set_modified(SystemTime::now() - STALE_LOCK * 2)?;
+        let _lock = LockFile::acquire(&path)?;
+        Ok(())
+    }
+}
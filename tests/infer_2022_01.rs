@@ -0,0 +1,26 @@
+use aocd::prelude::*;
+
+// The year and day are inferred from the name of this file, or it wouldn't compile.
+#[aocd("tests/inputs/2022-01.txt", part1 = 24000)]
+fn inferred() -> usize {
+    let input = input!();
+    let elves = input.split("\n\n").map(|elf| {
+        elf.lines()
+            .map(|l| l.parse::<usize>().unwrap())
+            .sum::<usize>()
+    });
+    submit!(1, elves.max().unwrap());
+    input.lines().count()
+}
+
+#[test]
+fn test_inferred_year_and_day() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || assert_eq!(inferred(), 14),
+    );
+}
@@ -0,0 +1,180 @@
+use aocd::prelude::*;
+
+#[aocd(2022, 1, "tests/inputs/2022-01.txt", part1 = 24000, part2 = 45000)]
+fn calorie_counting() -> usize {
+    let input = input!();
+    let mut elves: Vec<usize> = input
+        .split("\n\n")
+        .map(|elf| elf.lines().map(|l| l.parse::<usize>().unwrap()).sum())
+        .collect();
+    elves.sort_unstable_by(|a, b| b.cmp(a));
+    submit!(1, elves[0]);
+    submit!(2, elves[..3].iter().sum::<usize>());
+    elves.len()
+}
+
+#[test]
+fn test_expected_answers() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || assert_eq!(calorie_counting(), 5),
+    );
+}
+
+#[aocd(2022, 1, "tests/inputs/2022-01.txt", part1 = 24000)]
+fn with_argument(calories: Vec<usize>) -> Vec<usize> {
+    let mut owned = calories;
+    owned.push(input!().lines().count());
+    submit!(1, 24000);
+    owned
+}
+
+#[test]
+fn test_with_argument() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || assert_eq!(with_argument(vec![1]), vec![1, 14]),
+    );
+}
+
+#[aocd(2022, 1, [("tests/inputs/2022-01.txt", part1 = 24000), "tests/inputs/2022-01/a.txt"])]
+fn several_examples() -> usize {
+    let input = input!();
+    let elves: Vec<usize> = input
+        .split("\n\n")
+        .map(|elf| elf.lines().map(|l| l.parse::<usize>().unwrap()).sum())
+        .collect();
+    submit!(1, elves.iter().max().unwrap());
+    elves.len()
+}
+
+#[aocd(2022, 1, "tests/inputs/2022-01/*.txt")]
+fn glob_examples() -> usize {
+    input!().lines().count()
+}
+
+#[aocd(2022, 1, "tests/inputs/2022-01.txt")]
+fn opaque_result() -> impl std::fmt::Display {
+    if input!().is_empty() {
+        return "empty".to_string();
+    }
+    input!().lines().count().to_string()
+}
+
+#[test]
+fn test_several_examples() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || {
+            // The result is that of the last run.
+            assert_eq!(several_examples(), 2);
+            assert_eq!(glob_examples(), 7);
+            assert_eq!(opaque_result().to_string(), "14");
+        },
+    );
+}
+
+#[aocd(2022, 1, ["tests/inputs/2022-01/a.txt", "tests/inputs/2022-01.txt"])]
+fn failing_example() -> Result<usize, String> {
+    let elves = input!().split("\n\n").count();
+    if elves < 3 {
+        return Err(format!("Only {elves} elves"));
+    }
+    Ok(elves)
+}
+
+#[test]
+#[ignore = "Exits the process, so it is run by test_failing_example"]
+fn run_failing_example() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || {
+            let _ = failing_example();
+        },
+    );
+}
+
+#[test]
+fn test_failing_example() {
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["run_failing_example", "--exact", "--ignored", "--nocapture"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert!(stdout.contains("Only 2 elves"), "{stdout}");
+    // The runs stop at the error.
+    assert!(!stdout.contains("2022-01.txt"), "{stdout}");
+}
+
+#[aocd(2022, 1, ["tests/inputs/2022-01.txt", ("tests/inputs/2022-01/a.txt", part1 = 3)], tests)]
+fn with_tests() {
+    let input = input!();
+    let elves = input.split("\n\n").map(|elf| {
+        elf.lines()
+            .map(|l| l.parse::<usize>().unwrap())
+            .sum::<usize>()
+    });
+    submit!(1, elves.max().unwrap());
+}
+
+// `aocd_with_tests::example_2022_01` and `aocd_with_tests::example_a` are generated.
+
+#[aocd(2022, 1, "tests/inputs/2022-01/b.txt", part1 = 4, tests)]
+fn with_tests_and_result() -> Result<usize, std::num::ParseIntError> {
+    let input = input!();
+    let elves = input.split("\n\n").count();
+    for line in input.lines().filter(|line| !line.is_empty()) {
+        line.parse::<usize>()?;
+    }
+    submit!(1, elves);
+    Ok(elves)
+}
+
+#[aocd(2022, 1, "tests/inputs/2022-01.txt", part1 = 24000, embed)]
+fn embedded_example() -> usize {
+    let input = input!();
+    let elves = input.split("\n\n").map(|elf| {
+        elf.lines()
+            .map(|l| l.parse::<usize>().unwrap())
+            .sum::<usize>()
+    });
+    submit!(1, elves.max().unwrap());
+    input.lines().count()
+}
+
+#[test]
+fn test_embedded_example() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let other_dir = tempfile::tempdir().unwrap();
+    temp_env::with_vars(
+        [
+            ("AOC_SESSION", Some("test-session")),
+            ("AOC_CACHE_DIR", cache_dir.path().to_str()),
+        ],
+        || {
+            // Test files don't depend on the current directory, embedded or not.
+            let cwd = std::env::current_dir().unwrap();
+            std::env::set_current_dir(other_dir.path()).unwrap();
+            let lines = (embedded_example(), glob_examples());
+            std::env::set_current_dir(cwd).unwrap();
+            assert_eq!(lines, (14, 7));
+        },
+    );
+}
@@ -0,0 +1,9 @@
+#[test]
+fn test_compile_errors() {
+    std::env::remove_var("AOC_INFER_PATTERN");
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+
+    // The pattern is read by the macro at compile time, so these are compiled with it set.
+    std::env::set_var("AOC_INFER_PATTERN", "(?P<year>20\\d\\d");
+    trybuild::TestCases::new().compile_fail("tests/ui/infer_pattern/*.rs");
+}
@@ -0,0 +1,7 @@
+use aocd::prelude::*;
+
+// The crate root of the UI tests only has a Cargo.toml to use as a test file.
+#[aocd(2022, 1, ["Cargo.toml", "Cargo.toml"])]
+fn solve(_calories: Vec<usize>) {}
+
+fn main() {}
@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd(2025, 13)]
+fn main() {}
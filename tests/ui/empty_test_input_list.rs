@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd(2022, 1, [])]
+fn main() {}
@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd(2022, 1, "Cargo.toml", part1 = 1.5)]
+fn main() {}
@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd(2022, 1, part1 = 24000)]
+fn main() {}
@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd]
+fn main() {}
@@ -0,0 +1,6 @@
+use aocd::prelude::*;
+
+#[aocd(2022, 1)]
+fn main() {
+    submit!(3, 42);
+}
@@ -0,0 +1,4 @@
+use aocd::prelude::*;
+
+#[aocd(2014, 1)]
+fn main() {}